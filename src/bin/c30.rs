@@ -1,10 +1,9 @@
 extern crate common;
-extern crate byteorder;
 
-use common::md4::{md4_digest, md4_digest_chunk, HASH_SIZE, CHUNK_SIZE};
-use std::io::{Write, Cursor};
+use common::md4::{md4_digest, HASH_SIZE};
+use common::length_extension::{length_extend, HashAlg};
+use std::io::Write;
 use common::util::{random_bytes, print_hex};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 
 const ORIG_MSG: &[u8] = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
@@ -42,15 +41,6 @@ fn verify(key: &[u8], auth_msg: &[u8]) -> bool {
     auth.to_vec() == verify_auth
 }
 
-fn do_pad_msg(msg: &mut Vec<u8>, real_len: usize, fake_len: usize) {
-    msg.push(0x80);
-
-    let padding = ((CHUNK_SIZE - (real_len + 1) % CHUNK_SIZE) + CHUNK_SIZE - 8) % CHUNK_SIZE;
-
-    msg.extend_from_slice(&vec![0u8; padding]);
-    msg.write_u64::<LittleEndian>((fake_len * 8) as u64).unwrap();
-}
-
 fn main() {
     // Create original secret prefix message
     let (key, orig_auth_msg) = create(&ORIG_MSG);
@@ -62,42 +52,28 @@ fn main() {
     assert_eq!(verify(&key, &orig_auth_msg), true);
 
     let hash = &orig_auth_msg[..HASH_SIZE];
-    let mut hash_cursor = Cursor::new(hash);
-    let mut abcd = [0u32; 4];
-    for val in abcd.iter_mut() {
-        *val = hash_cursor.read_u32::<LittleEndian>().unwrap();
-    }
-
-    // add padding and length to the modified message
     let orig_msg = &orig_auth_msg[HASH_SIZE..];
-    let orig_msg_len = orig_msg.len() + 10; // orignal length + size of secret key
-    let mut orig_msg_padded = Vec::new();
-    orig_msg_padded.extend_from_slice(&orig_msg);
-    do_pad_msg(&mut orig_msg_padded, orig_msg_len, orig_msg_len);
-
-    let mut evil_msg = Vec::new();
-    evil_msg.extend_from_slice(EVIL_SUFFIX);
-    do_pad_msg(&mut evil_msg, EVIL_SUFFIX.len(), orig_msg_padded.len() + 10 + EVIL_SUFFIX.len());
 
-    println!("Evil msg");
-    print_hex(&evil_msg);
+    // The key length is unknown so try every length up to 64 bytes
+    let candidates = length_extend(HashAlg::Md4, hash, orig_msg, 0..64, EVIL_SUFFIX);
 
-    // Hash new evil message
-    md4_digest_chunk(&mut abcd, &evil_msg);
+    let mut found = None;
+    for (key_len, (evil_msg, evil_hash)) in candidates.into_iter().enumerate() {
+        // Prepend the hash
+        let mut evil_auth_msg = evil_hash;
+        evil_auth_msg.extend_from_slice(&evil_msg);
 
-    // Prepend the hash
-    let mut evil_auth_msg = Vec::new();
-    for val in abcd.iter() {
-        evil_auth_msg.write_u32::<LittleEndian>(*val).unwrap();
+        if verify(&key, &evil_auth_msg) {
+            println!("Key length is {}", key_len);
+            found = Some(evil_auth_msg);
+            break;
+        }
     }
-    evil_auth_msg.extend_from_slice(&orig_msg_padded);
-    evil_auth_msg.extend_from_slice(&EVIL_SUFFIX);
+
+    let evil_auth_msg = found.expect("no key length worked");
 
     println!("Evil auth msg");
     print_hex(&evil_auth_msg);
 
-    // Make sure it still verifies
-    assert_eq!(verify(&key, &evil_auth_msg), true);
-
     println!("OK");
 }
\ No newline at end of file
@@ -4,7 +4,12 @@ extern crate hex;
 
 use reqwest::Client;
 use std::time::Instant;
-use common::sha1::HASH_SIZE;
+use common::digest::Digest;
+use common::sha1::Sha1;
+
+// must match the hash the server uses for its HMAC
+type MacDigest = Sha1;
+const HASH_SIZE: usize = MacDigest::OUTPUT_SIZE;
 
 fn time_request(client: &Client, test_hash: &str) -> u64 {
     let start = Instant::now();
@@ -10,9 +10,15 @@ extern crate lazy_static;
 use rocket::http::Status;
 use std::fs;
 use std::{thread, time};
-use common::sha1::{sha1_hmac, HASH_SIZE};
+use common::digest::Digest;
+use common::hmac::hmac;
+use common::sha1::Sha1;
 use common::util::random_bytes;
 
+// any of the crate's hashes can be plugged in here
+type MacDigest = Sha1;
+const HASH_SIZE: usize = MacDigest::OUTPUT_SIZE;
+
 lazy_static! {
     static ref HMAC_KEY: Vec<u8> = random_bytes(HASH_SIZE);
 }
@@ -52,7 +58,7 @@ fn test_handler(file: String, signature: String) -> Status {
         return Status::BadRequest;
     }
 
-    let sig_check = sha1_hmac(&HMAC_KEY, &data);
+    let sig_check = hmac::<MacDigest>(&HMAC_KEY, &data);
 
     if !insecure_compare(&sig_check, &sig_decode) {
         println!("{} {}", hex::encode(&sig_check), hex::encode(&sig_decode));
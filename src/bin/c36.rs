@@ -10,7 +10,12 @@ use std::sync::mpsc;
 use std::sync::mpsc::{Sender, Receiver};
 use common::dh::{gen_dh_pair, P, G};
 use common::util::random_bytes;
-use common::crypto_helper::{hmac_sha256, sha256};
+use common::digest::Digest;
+use common::hmac::hmac;
+use common::sha256::Sha256;
+
+// hash used for both the SRP values and the final HMAC
+type SrpDigest = Sha256;
 
 
 enum Msg {
@@ -37,7 +42,7 @@ fn combine_pub_keys(a_pub: &Int, b_pub: &Int) -> Int {
     let mut sha_input = Vec::new();
     sha_input.extend_from_slice(&a_pub.to_str_radix(16, false).as_bytes()); 
     sha_input.extend_from_slice(&b_pub.to_str_radix(16, false).as_bytes()); 
-    let u_h = sha256(&sha_input);
+    let u_h = SrpDigest::digest(&sha_input);
     
     Int::from_str_radix(&hex::encode(&u_h), 16).unwrap()
 }
@@ -46,7 +51,7 @@ fn combine_salt_pw(salt: &[u8], pw: &[u8]) -> Int {
     let mut sha_input = Vec::new();
     sha_input.extend_from_slice(&salt);
     sha_input.extend_from_slice(&pw);
-    let x_h = sha256(&sha_input);
+    let x_h = SrpDigest::digest(&sha_input);
     // there's probably a less stupid way to do this but this works for now
     Int::from_str_radix(&hex::encode(&x_h), 16).unwrap()
 }
@@ -66,8 +71,8 @@ fn client_node(tx: Sender<Msg>, rx: Receiver<Msg>) {
                 let k = Int::from(K);
 
                 let check_val = (b_pub - k * G.pow_mod(&x, &P)).pow_mod(&(&priv_key + u * x), &P); 
-                let hmac_key = sha256(&check_val.to_str_radix(16, false).as_bytes());
-                let check_hmac = hmac_sha256(&hmac_key, &salt);
+                let hmac_key = SrpDigest::digest(&check_val.to_str_radix(16, false).as_bytes());
+                let check_hmac = hmac::<SrpDigest>(&hmac_key, &salt);
 
                 tx.send(Msg::Check { client_check: check_hmac }).unwrap();
             },
@@ -106,8 +111,8 @@ fn server_node(tx: Sender<Msg>, rx: Receiver<Msg>) {
                 let u = combine_pub_keys(&a_pub, &pub_key);
 
                 let check_val = (a_pub * v.pow_mod(&u, &P)).pow_mod(&priv_key, &P);
-                let hmac_key = sha256(&check_val.to_str_radix(16, false).as_bytes());
-                check_hmac = Some(hmac_sha256(&hmac_key, &salt));
+                let hmac_key = SrpDigest::digest(&check_val.to_str_radix(16, false).as_bytes());
+                check_hmac = Some(hmac::<SrpDigest>(&hmac_key, &salt));
 
                 tx.send(Msg::SaltPubKey { salt: salt.clone(), b_pub: pub_key.clone() }).unwrap();
             },
@@ -0,0 +1,31 @@
+extern crate common;
+extern crate ramp;
+
+use common::rsa::{gen_rsa_pair, encrypt_rsa, pkcs1_v15_pad_encrypt, pkcs1_v15_unpad};
+use common::bleichenbacher::{RSAPaddingOracle, bleichenbacher_recover_block};
+use common::ops::IntOpsExt;
+
+use ramp::Int;
+
+const MSG: &[u8] = b"kick it, CC";
+
+fn main() {
+    let (pub_key, priv_key) = gen_rsa_pair(768);
+    println!("n = {}", pub_key.n);
+
+    let k = (pub_key.n.bit_length() as usize + 7) / 8;
+
+    let m = Int::from_bytes(&pkcs1_v15_pad_encrypt(MSG, k).unwrap());
+    let c = encrypt_rsa(&pub_key, &m).unwrap();
+    println!("c = {}", c);
+
+    let oracle = RSAPaddingOracle::new(priv_key, &pub_key);
+
+    let block = bleichenbacher_recover_block(&oracle, &pub_key, &c);
+    println!("recovered block = {}", hex::encode(&block));
+
+    let recovered = pkcs1_v15_unpad(&block).unwrap();
+    println!("recovered message = {}", String::from_utf8_lossy(&recovered));
+
+    assert_eq!(recovered, MSG);
+}
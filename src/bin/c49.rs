@@ -0,0 +1,141 @@
+extern crate common;
+
+use common::cbc_mac::{cbc_mac, verify_cbc_mac, forge_iv, forge_concatenation, MAC_SIZE};
+use common::crypto_helper::BLOCK_SIZE;
+use common::util::random_bytes;
+
+const VICTIM_ID: u32 = 1;
+const ATTACKER_ID: u32 = 2;
+
+// The bank's API server. Clients that share its key build signed requests
+// of the form msg || iv || mac (first protocol) or msg || mac (second).
+struct Server {
+    key: Vec<u8>,
+}
+
+impl Server {
+    // message || IV || MAC with the IV chosen by the client
+    fn handle_v1(&self, request: &[u8]) -> Option<(u32, u32, u64)> {
+        let msg_len = request.len() - BLOCK_SIZE - MAC_SIZE;
+        let (msg, rest) = request.split_at(msg_len);
+        let (iv, mac) = rest.split_at(BLOCK_SIZE);
+
+        if !verify_cbc_mac(&self.key, iv, msg, mac) {
+            return None;
+        }
+
+        let msg = String::from_utf8_lossy(msg);
+        let mut from = None;
+        let mut to = None;
+        let mut amount = None;
+
+        for param in msg.split('&') {
+            let mut kv = param.splitn(2, '=');
+
+            match (kv.next(), kv.next()) {
+                (Some("from"), Some(v)) => from = v.parse().ok(),
+                (Some("to"), Some(v)) => to = v.parse().ok(),
+                (Some("amount"), Some(v)) => amount = v.parse().ok(),
+                _ => (),
+            }
+        }
+
+        Some((from?, to?, amount?))
+    }
+
+    // message || MAC with a fixed zero IV. Malformed transactions in the
+    // list are skipped rather than rejecting the whole request.
+    fn handle_v2(&self, request: &[u8]) -> Option<(u32, Vec<(u32, u64)>)> {
+        let (msg, mac) = request.split_at(request.len() - MAC_SIZE);
+
+        if !verify_cbc_mac(&self.key, &[0u8; BLOCK_SIZE], msg, mac) {
+            return None;
+        }
+
+        let msg = String::from_utf8_lossy(msg);
+        let mut parts = msg.splitn(2, "&tx_list=");
+
+        let from = parts.next()?.trim_start_matches("from=").parse().ok()?;
+        let txs = parts.next()?
+            .split(';')
+            .filter_map(|tx| {
+                let mut to_amount = tx.splitn(2, ':');
+                let to = to_amount.next()?.parse().ok()?;
+                let amount = to_amount.next()?.parse().ok()?;
+
+                Some((to, amount))
+            })
+            .collect();
+
+        Some((from, txs))
+    }
+}
+
+// The web client signs requests for the logged in user only
+fn sign_v1(key: &[u8], from: u32, to: u32, amount: u64) -> Vec<u8> {
+    let iv = random_bytes(BLOCK_SIZE);
+    let mut request = format!("from={}&to={}&amount={}", from, to, amount).into_bytes();
+    let mac = cbc_mac(key, &iv, &request);
+
+    request.extend_from_slice(&iv);
+    request.extend_from_slice(&mac);
+
+    request
+}
+
+fn sign_v2(key: &[u8], from: u32, txs: &[(u32, u64)]) -> Vec<u8> {
+    let tx_list: Vec<_> = txs.iter().map(|(to, amount)| format!("{}:{}", to, amount)).collect();
+    let mut request = format!("from={}&tx_list={}", from, tx_list.join(";")).into_bytes();
+    let mac = cbc_mac(key, &[0u8; BLOCK_SIZE], &request);
+
+    request.extend_from_slice(&mac);
+
+    request
+}
+
+fn main() {
+    let server = Server { key: random_bytes(16) };
+
+    // Attacker controlled IV: sign a transfer to ourselves from our own
+    // account and then rewrite the first block to come from the victim
+    let request = sign_v1(&server.key, ATTACKER_ID, ATTACKER_ID, 1_000_000);
+    let msg_len = request.len() - BLOCK_SIZE - MAC_SIZE;
+    let (msg, rest) = request.split_at(msg_len);
+    let (iv, mac) = rest.split_at(BLOCK_SIZE);
+
+    let new_first_block = format!("from={}&to={}&amou", VICTIM_ID, ATTACKER_ID);
+    let (forged_msg, forged_iv) = forge_iv(msg, iv, new_first_block.as_bytes());
+
+    let mut forged = forged_msg;
+    forged.extend_from_slice(&forged_iv);
+    forged.extend_from_slice(mac);
+
+    println!("Forged request: {:?}", String::from_utf8_lossy(&forged[..msg_len]));
+
+    let result = server.handle_v1(&forged);
+    println!("Server accepted {:?}", result);
+    assert_eq!(result, Some((VICTIM_ID, ATTACKER_ID, 1_000_000)));
+
+    // Fixed IV: capture one of the victim's requests and glue a request we
+    // signed for ourselves onto it
+    let captured = sign_v2(&server.key, VICTIM_ID, &[(3, 5), (4, 10)]);
+    let (victim_msg, victim_mac) = captured.split_at(captured.len() - MAC_SIZE);
+
+    // The first block of our request gets garbled, so the transfer we care
+    // about has to be in the following blocks
+    let own = sign_v2(&server.key, ATTACKER_ID, &[(0, 0), (ATTACKER_ID, 1_000_000)]);
+    let (own_msg, own_mac) = own.split_at(own.len() - MAC_SIZE);
+
+    let mut forged = forge_concatenation(victim_msg, victim_mac, own_msg);
+    forged.extend_from_slice(own_mac);
+
+    println!("Forged request: {:?}", String::from_utf8_lossy(&forged[..forged.len() - MAC_SIZE]));
+
+    let (from, txs) = server.handle_v2(&forged).expect("forged request was rejected");
+    println!("Server accepted from {} {:?}", from, txs);
+
+    assert_eq!(from, VICTIM_ID);
+    assert!(txs.contains(&(ATTACKER_ID, 1_000_000)));
+
+    println!("OK");
+}
@@ -0,0 +1,40 @@
+extern crate common;
+
+use common::cbc_mac::{cbc_mac, cbc_mac_collision};
+use common::crypto_helper::BLOCK_SIZE;
+
+const KEY: &[u8] = b"YELLOW SUBMARINE";
+const ORIG_JS: &[u8] = b"alert('MZA who was that?');\n";
+const EVIL_JS: &str = "alert('Ayo, the Wu is back!');";
+
+fn main() {
+    let iv = [0u8; BLOCK_SIZE];
+    let target = cbc_mac(KEY, &iv, ORIG_JS);
+
+    println!("Original hash: {}", hex::encode(&target));
+
+    // Everything after the "//" is a comment until the newline at the end,
+    // so the glue block can be anything but a line break. Pad the prefix
+    // with spaces until we get a glue block without one.
+    let mut prefix = EVIL_JS.to_string();
+
+    let forged = loop {
+        let mut commented = prefix.clone();
+        commented.push_str("//");
+
+        let forged = cbc_mac_collision(KEY, &iv, &target, commented.as_bytes(), b"\n");
+
+        if !forged[..forged.len() - 1].iter().any(|b| *b == b'\n' || *b == b'\r') {
+            break forged;
+        }
+
+        prefix.push(' ');
+    };
+
+    println!("Forged snippet: {:?}", String::from_utf8_lossy(&forged));
+    println!("Forged hash: {}", hex::encode(&cbc_mac(KEY, &iv, &forged)));
+
+    assert_eq!(cbc_mac(KEY, &iv, &forged), target);
+
+    println!("OK");
+}
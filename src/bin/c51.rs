@@ -0,0 +1,19 @@
+extern crate common;
+
+use common::crime::{SessionOracle, OracleCipher, recover_secret, SESSION_ID, COOKIE_PREFIX, BASE64_ALPHABET};
+use std::time::Instant;
+
+fn main() {
+    for cipher in [OracleCipher::Ctr, OracleCipher::Cbc].iter() {
+        let oracle = SessionOracle::new(SESSION_ID, *cipher);
+
+        let start = Instant::now();
+        let recovered = recover_secret(&oracle, COOKIE_PREFIX, BASE64_ALPHABET, b'\r', 64);
+
+        println!("{:?}: recovered {} in {:?}", cipher, String::from_utf8_lossy(&recovered), start.elapsed());
+
+        assert_eq!(recovered, SESSION_ID);
+    }
+
+    println!("OK");
+}
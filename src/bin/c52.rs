@@ -0,0 +1,39 @@
+extern crate common;
+
+use common::md::Compression;
+use common::multicollision::{Multicollision, cascade_collision};
+use common::toy_hash::{ToyCompression, toy_digest};
+use std::time::Instant;
+
+const F_BITS: usize = 16;
+const G_BITS: usize = 32;
+
+fn main() {
+    let f = ToyCompression::new(F_BITS);
+    let g = ToyCompression::new(G_BITS);
+
+    // 2^n collisions for the price of n
+    let start = Instant::now();
+    let multicollision = Multicollision::new(&f, &f.initial_state(), 8);
+    println!("{} colliding messages in {:?}", multicollision.count(), start.elapsed());
+
+    let digest = toy_digest(F_BITS, &multicollision.message(0));
+    for index in 0..multicollision.count() {
+        assert_eq!(toy_digest(F_BITS, &multicollision.message(index)), digest);
+    }
+
+    // f || g is barely stronger than g
+    let start = Instant::now();
+    let (m1, m2) = cascade_collision(&f, &g);
+    println!("Cascade collision in {:?}", start.elapsed());
+
+    println!("m1 = {}", hex::encode(&m1));
+    println!("m2 = {}", hex::encode(&m2));
+    println!("f = {} g = {}", hex::encode(&toy_digest(F_BITS, &m1)), hex::encode(&toy_digest(G_BITS, &m1)));
+
+    assert_ne!(m1, m2);
+    assert_eq!(toy_digest(F_BITS, &m1), toy_digest(F_BITS, &m2));
+    assert_eq!(toy_digest(G_BITS, &m1), toy_digest(G_BITS, &m2));
+
+    println!("OK");
+}
@@ -0,0 +1,32 @@
+extern crate common;
+
+use common::md::md_digest;
+use common::second_preimage::second_preimage;
+use common::toy_hash::ToyCompression;
+use common::util::random_bytes;
+use std::time::Instant;
+
+const HASH_BITS: usize = 32;
+// The target is 2^K blocks long
+const K: usize = 16;
+
+fn main() {
+    let compression = ToyCompression::new(HASH_BITS);
+    let target = random_bytes((1 << K) * 16);
+
+    println!("Target digest {}", hex::encode(&md_digest(compression, &target)));
+
+    let start = Instant::now();
+    let forged = second_preimage(&compression, &target, K);
+    println!("Found second preimage in {:?}", start.elapsed());
+
+    println!("Forged digest {}", hex::encode(&md_digest(compression, &forged)));
+
+    let diff_blocks = forged.chunks(16).zip(target.chunks(16)).filter(|(a, b)| a != b).count();
+    println!("{} of {} blocks differ", diff_blocks, target.len() / 16);
+
+    assert_eq!(forged.len(), target.len());
+    assert_eq!(md_digest(compression, &forged), md_digest(compression, &target));
+
+    println!("OK");
+}
@@ -0,0 +1,34 @@
+extern crate common;
+
+use common::herding::DiamondStructure;
+use common::md::md_digest;
+use common::toy_hash::ToyCompression;
+
+const HASH_BITS: usize = 24;
+const PREFIX_BLOCKS: usize = 4;
+const PREDICTION: &str = "2018 World Series: Red Sox beat Dodgers 4-1. MVP: Steve Pearce.";
+
+fn main() {
+    let compression = ToyCompression::new(HASH_BITS);
+
+    let mut prefix = PREDICTION.as_bytes().to_vec();
+    prefix.resize(PREFIX_BLOCKS * 16, b' ');
+
+    // Bigger diamonds cost more up front but make the linking block cheaper
+    for k in [4, 6, 8].iter() {
+        let diamond = DiamondStructure::new(&compression, *k, PREFIX_BLOCKS);
+        let commitment = diamond.commitment(&compression);
+
+        let (msg, link_stats) = diamond.herd(&compression, &prefix);
+
+        println!("k = {:2}: commitment {} build {:>9} compressions in {:?}, link {:>9} compressions in {:?}",
+                 k, hex::encode(&commitment),
+                 diamond.build_stats.compressions, diamond.build_stats.time,
+                 link_stats.compressions, link_stats.time);
+
+        assert!(msg.starts_with(PREDICTION.as_bytes()));
+        assert_eq!(md_digest(compression, &msg), commitment);
+    }
+
+    println!("OK");
+}
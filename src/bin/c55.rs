@@ -0,0 +1,38 @@
+extern crate common;
+
+use common::md4::md4_digest;
+use common::md4_collision::find_collision_with;
+use std::time::Instant;
+
+const REPORT_INTERVAL: u64 = 1 << 18;
+
+fn main() {
+    let start = Instant::now();
+    let mut later_satisfied = 0;
+    let mut later_total = 0;
+
+    let (m1, m2) = find_collision_with(|attempt, report| {
+        assert_eq!(report.round1_satisfied, report.round1_total);
+
+        later_satisfied += report.later_satisfied;
+        later_total += report.later_total;
+
+        if attempt % REPORT_INTERVAL == 0 {
+            println!("{} attempts, {:.2}% of round 2/3 conditions satisfied on average",
+                     attempt, 100.0 * later_satisfied as f64 / later_total as f64);
+        }
+    });
+
+    println!("Found collision in {:?}", start.elapsed());
+    println!("m1 = {}", hex::encode(&m1));
+    println!("m2 = {}", hex::encode(&m2));
+
+    let (h1, h2) = (md4_digest(&m1), md4_digest(&m2));
+    println!("MD4(m1) = {}", hex::encode(&h1));
+    println!("MD4(m2) = {}", hex::encode(&h2));
+
+    assert_ne!(m1, m2);
+    assert_eq!(h1, h2);
+
+    println!("OK");
+}
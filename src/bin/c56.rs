@@ -0,0 +1,21 @@
+extern crate common;
+
+use common::rc4_bias::{CookieOracle, recover_cookie, COOKIE};
+use std::time::Instant;
+
+const SAMPLES: u64 = 1 << 24;
+const THREADS: u64 = 8;
+
+fn main() {
+    let cookie = base64::decode(COOKIE).unwrap();
+    let oracle = CookieOracle::new(&cookie);
+
+    let start = Instant::now();
+    let recovered = recover_cookie(oracle, SAMPLES, THREADS);
+
+    println!("Recovered {:?} in {:?}", String::from_utf8_lossy(&recovered), start.elapsed());
+
+    assert_eq!(recovered, cookie);
+
+    println!("OK");
+}
@@ -0,0 +1,30 @@
+extern crate common;
+
+use common::dh::CH57_GROUP;
+use common::ops::crt;
+use common::prime::factor_small;
+use common::subgroup_confinement::{Bob, collect_residues};
+
+const FACTOR_BOUND: u64 = 1 << 16;
+
+fn main() {
+    let group = &*CH57_GROUP;
+    let bob = Bob::new(group);
+
+    println!("Small factors of j: {:?}", factor_small(&group.cofactor(), FACTOR_BOUND));
+
+    let residues = collect_residues(group, &bob, FACTOR_BOUND);
+
+    for (x, r) in residues.iter() {
+        println!("x = {} mod {}", x, r);
+    }
+
+    let (x, modulus) = crt(&residues);
+    assert!(modulus > group.q);
+
+    println!("Recovered x = {}", x);
+
+    assert!(bob.check_key(&x));
+
+    println!("OK");
+}
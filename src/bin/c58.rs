@@ -0,0 +1,39 @@
+extern crate common;
+extern crate ramp;
+
+use common::dh::CH58_GROUP;
+use common::kangaroo::{kangaroo, recover_key};
+use common::subgroup_confinement::Bob;
+use ramp::Int;
+use std::time::Instant;
+
+const FACTOR_BOUND: u64 = 1 << 16;
+
+fn main() {
+    let group = &*CH58_GROUP;
+
+    let examples = [
+        ("7760073848032689505395005705677365876654629189298052775754597607446617558600394076764814236081991643094239886772481052254010323780165093955236429914607119", 20),
+        ("9388897478013399550694114614498790691034187453089355259602614074132918843899833277397448144245883225611726912025846772975325932794909655215329941809013733", 40),
+    ];
+
+    for (y, bits) in examples.iter() {
+        let y = Int::from_str_radix(y, 10).unwrap();
+        let start = Instant::now();
+
+        let x = kangaroo(&group.g, &y, &group.p, &Int::from(0), &(Int::from(1) << *bits)).unwrap();
+        println!("Index of y in [0, 2^{}]: {} ({:?})", bits, x, start.elapsed());
+
+        assert_eq!(group.g.pow_mod(&x, &group.p), y);
+    }
+
+    let bob = Bob::new(group);
+    let start = Instant::now();
+
+    let x = recover_key(group, &bob, FACTOR_BOUND).unwrap();
+    println!("Recovered Bob's private key {} in {:?}", x, start.elapsed());
+
+    assert!(bob.check_key(&x));
+
+    println!("OK");
+}
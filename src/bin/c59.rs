@@ -0,0 +1,29 @@
+extern crate common;
+
+use common::ec::SET8_CURVE;
+use common::invalid_curve::{EcdhBob, collect_residues};
+use common::ops::crt;
+use std::time::Instant;
+
+const FACTOR_BOUND: u64 = 1 << 16;
+
+fn main() {
+    let curve = &*SET8_CURVE;
+    let bob = EcdhBob::new(curve);
+
+    let start = Instant::now();
+    let residues = collect_residues(&bob, FACTOR_BOUND);
+
+    for (k, r) in residues.iter() {
+        println!("k = {} mod {}", k, r);
+    }
+
+    let (k, modulus) = crt(&residues);
+    assert!(modulus > curve.order);
+
+    println!("Recovered k = {} in {:?}", k, start.elapsed());
+
+    assert!(bob.check_key(&k));
+
+    println!("OK");
+}
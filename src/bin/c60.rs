@@ -0,0 +1,29 @@
+extern crate common;
+extern crate ramp;
+
+use common::montgomery::SET8_MONTGOMERY;
+use common::twist_attack::{MontgomeryBob, recover_key};
+use ramp::Int;
+use std::time::Instant;
+
+const FACTOR_BOUND: u64 = 1 << 22;
+
+fn main() {
+    let curve = &*SET8_MONTGOMERY;
+
+    // Bob's ladder on the curve from challenge 60
+    let u = Int::from_str_radix("76600469441198017145391791613091732004", 10).unwrap();
+    println!("ladder(u, 11) = {}", curve.ladder(&u, &Int::from(11)));
+    println!("u on curve: {}", curve.is_on_curve(&u));
+    println!("Twist order: {}", curve.twist_order());
+
+    let bob = MontgomeryBob::new(curve);
+    let start = Instant::now();
+
+    let k = recover_key(curve, &bob, FACTOR_BOUND).unwrap();
+    println!("Recovered k = {} in {:?}", k, start.elapsed());
+
+    assert!(bob.check_key(&k));
+
+    println!("OK");
+}
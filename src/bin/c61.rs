@@ -0,0 +1,30 @@
+extern crate common;
+
+use common::dsks::{ecdsa_dsks, rsa_dsks};
+use common::ec::SET8_CURVE;
+use common::ecdsa::{gen_ecdsa_pair, sign_ecdsa, verify_ecdsa};
+use common::rsa::{gen_rsa_pair, sign_rsa, verify_rsa};
+use std::time::Instant;
+
+fn main() {
+    let msg = b"send 100 coins to mallory";
+
+    let (alice_pub, alice_priv) = gen_ecdsa_pair(&SET8_CURVE);
+    let sig = sign_ecdsa(&alice_priv, msg);
+    assert!(verify_ecdsa(&alice_pub, msg, &sig));
+
+    let (eve_pub, _) = ecdsa_dsks(&alice_pub, msg, &sig);
+    println!("ECDSA: new generator {:?}", eve_pub.curve.base);
+    assert!(verify_ecdsa(&eve_pub, msg, &sig));
+
+    let (alice_pub, alice_priv) = gen_rsa_pair(1024);
+    let sig = sign_rsa(&alice_priv, msg);
+    verify_rsa(&alice_pub, msg, &sig).unwrap();
+
+    let start = Instant::now();
+    let (eve_pub, _) = rsa_dsks(&alice_pub, msg, &sig);
+    println!("RSA: new key in {:?}\nn' = {}\ne' = {}", start.elapsed(), eve_pub.n, eve_pub.e);
+    verify_rsa(&eve_pub, msg, &sig).unwrap();
+
+    println!("OK");
+}
@@ -0,0 +1,30 @@
+extern crate common;
+
+use common::ec::SET8_CURVE;
+use common::ecdsa::gen_ecdsa_pair;
+use common::hnp::{sign_ecdsa_biased, recover_ecdsa_key};
+use std::time::Instant;
+
+const BIASED_BITS: u32 = 8;
+const SIGNATURES: usize = 22;
+
+fn main() {
+    let (pub_key, priv_key) = gen_ecdsa_pair(&SET8_CURVE);
+
+    let sigs: Vec<_> = (0..SIGNATURES)
+        .map(|i| {
+            let msg = format!("attack at dawn, signature {}", i).into_bytes();
+            let sig = sign_ecdsa_biased(&priv_key, &msg, BIASED_BITS);
+
+            (msg, sig)
+        })
+        .collect();
+
+    let start = Instant::now();
+    let d = recover_ecdsa_key(&pub_key, &sigs, BIASED_BITS).expect("no key found");
+
+    println!("Recovered d = {} in {:?}", d, start.elapsed());
+    assert_eq!(d, priv_key.d);
+
+    println!("OK");
+}
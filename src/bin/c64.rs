@@ -0,0 +1,27 @@
+extern crate common;
+
+use common::gcm_short_tag::{ShortTagOracle, TagOracle, recover_hash_key_with, forge_tag};
+use common::gf128::BLOCK_SIZE;
+use common::util::random_bytes;
+use std::time::Instant;
+
+const TAG_LEN: usize = 4;
+const N: usize = 17;
+
+fn main() {
+    let oracle = ShortTagOracle::new(TAG_LEN);
+    let (ct, tag) = oracle.seal(&random_bytes(BLOCK_SIZE << N));
+
+    let start = Instant::now();
+    let h = recover_hash_key_with(&oracle, &ct, &tag, N, |dim, queries| {
+        println!("{:?}: {} queries, {} bits of h left", start.elapsed(), queries, dim);
+    });
+
+    println!("Recovered H = {:032x}", h.0);
+    assert!(oracle.check_hash_key(h));
+
+    let new_ct = b"an entirely made up ciphertext".to_vec();
+    assert!(oracle.accepts(&new_ct, &forge_tag(h, &ct, &tag, &new_ct)));
+
+    println!("OK");
+}
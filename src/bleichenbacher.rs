@@ -0,0 +1,202 @@
+// Bleichenbacher's PKCS #1 v1.5 padding oracle attack
+// http://archiv.infsec.ethz.ch/education/fs08/secsem/bleichenbacher98.pdf
+
+use ramp::int::Int;
+use crate::rsa::{RSAPubKey, RSAPrivKey, decrypt_rsa};
+use crate::ops::IntOpsExt;
+
+pub trait PaddingOracle {
+    // true if the decrypted ciphertext starts with 00 02
+    fn is_conforming(&self, c: &Int) -> bool;
+}
+
+pub struct RSAPaddingOracle {
+    priv_key: RSAPrivKey,
+    k: usize,
+}
+
+impl RSAPaddingOracle {
+    pub fn new(priv_key: RSAPrivKey, pub_key: &RSAPubKey) -> Self {
+        let k = (pub_key.n.bit_length() as usize + 7) / 8;
+
+        RSAPaddingOracle { priv_key, k }
+    }
+}
+
+impl PaddingOracle for RSAPaddingOracle {
+    fn is_conforming(&self, c: &Int) -> bool {
+        let m = decrypt_rsa(&self.priv_key, c).unwrap();
+
+        // to_bytes strips the leading zero so a conforming block is k - 1 bytes
+        let block = m.to_bytes();
+
+        block.len() == self.k - 1 && block[0] == 0x02
+    }
+}
+
+fn ceil_div(a: &Int, b: &Int) -> Int {
+    let (q, r) = a.divmod(b);
+
+    if r > 0 {
+        q + 1
+    } else {
+        q
+    }
+}
+
+fn floor_div(a: &Int, b: &Int) -> Int {
+    let (q, r) = a.divmod(b);
+
+    if r < 0 {
+        q - 1
+    } else {
+        q
+    }
+}
+
+// Insert an interval into the set, merging any overlapping intervals
+fn merge_interval(intervals: &mut Vec<(Int, Int)>, a: Int, b: Int) {
+    let (mut a, mut b) = (a, b);
+
+    let mut i = 0;
+    while i < intervals.len() {
+        let overlaps = intervals[i].0 <= b && a <= intervals[i].1;
+
+        if overlaps {
+            let (old_a, old_b) = intervals.remove(i);
+
+            if old_a < a {
+                a = old_a;
+            }
+
+            if old_b > b {
+                b = old_b;
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    intervals.push((a, b));
+}
+
+pub fn bleichenbacher_attack<O: PaddingOracle>(oracle: &O, pub_key: &RSAPubKey, c: &Int) -> Int {
+    let (n, e) = (&pub_key.n, &pub_key.e);
+    let k = (n.bit_length() as usize + 7) / 8;
+
+    let big_b = Int::from(2).pow(8 * (k - 2));
+    let b2: Int = &big_b * 2;
+    let b3: Int = &big_b * 3;
+
+    let try_s = |s: &Int| oracle.is_conforming(&((c * s.pow_mod(e, n)) % n));
+
+    // Step 1: blinding. The ciphertext is assumed to be PKCS conforming
+    // already so s0 is 1.
+    assert!(oracle.is_conforming(c));
+
+    let mut intervals = vec![(b2.clone(), &b3 - 1)];
+    let mut s = Int::from(0);
+    let mut i = 1;
+
+    loop {
+        if i == 1 {
+            // Step 2.a: starting the search
+            s = ceil_div(n, &b3);
+
+            while !try_s(&s) {
+                s += 1;
+            }
+        } else if intervals.len() > 1 {
+            // Step 2.b: searching with more than one interval left
+            s += 1;
+
+            while !try_s(&s) {
+                s += 1;
+            }
+        } else {
+            // Step 2.c: searching with one interval left
+            let (a, b) = intervals[0].clone();
+
+            let mut r = ceil_div(&((&b * &s - &b2) * 2), n);
+
+            'search: loop {
+                let s_lo = ceil_div(&(&b2 + &r * n), &b);
+                let s_hi = ceil_div(&(&b3 + &r * n), &a);
+
+                let mut s_test = s_lo;
+                while s_test < s_hi {
+                    if try_s(&s_test) {
+                        s = s_test;
+                        break 'search;
+                    }
+
+                    s_test += 1;
+                }
+
+                r += 1;
+            }
+        }
+
+        // Step 3: narrowing the set of solutions
+        let mut new_intervals = Vec::new();
+
+        for (a, b) in intervals.iter() {
+            let mut r = ceil_div(&(a * &s - &b3 + 1), n);
+            let r_hi = floor_div(&(b * &s - &b2), n);
+
+            while r <= r_hi {
+                let lo = ceil_div(&(&b2 + &r * n), &s);
+                let hi = floor_div(&(&b3 - 1 + &r * n), &s);
+
+                let lo = if &lo > a { lo } else { a.clone() };
+                let hi = if &hi < b { hi } else { b.clone() };
+
+                if lo <= hi {
+                    merge_interval(&mut new_intervals, lo, hi);
+                }
+
+                r += 1;
+            }
+        }
+
+        intervals = new_intervals;
+
+        // Step 4: computing the solution
+        if intervals.len() == 1 && intervals[0].0 == intervals[0].1 {
+            return intervals[0].0.clone();
+        }
+
+        i += 1;
+    }
+}
+
+// Convenience wrapper that returns the recovered message with the
+// PKCS #1 v1.5 padding still attached
+pub fn bleichenbacher_recover_block<O: PaddingOracle>(oracle: &O, pub_key: &RSAPubKey, c: &Int) -> Vec<u8> {
+    let k = (pub_key.n.bit_length() as usize + 7) / 8;
+
+    bleichenbacher_attack(oracle, pub_key, c).to_bytes_len(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::{gen_rsa_pair, encrypt_rsa, pkcs1_v15_pad_encrypt, pkcs1_v15_unpad};
+
+    #[test]
+    fn test_bleichenbacher_256() {
+        let msg = b"kick it, CC";
+
+        let (pub_key, priv_key) = gen_rsa_pair(256);
+        let k = (pub_key.n.bit_length() as usize + 7) / 8;
+
+        let m = Int::from_bytes(&pkcs1_v15_pad_encrypt(msg, k).unwrap());
+        let c = encrypt_rsa(&pub_key, &m).unwrap();
+
+        let oracle = RSAPaddingOracle::new(priv_key, &pub_key);
+
+        let block = bleichenbacher_recover_block(&oracle, &pub_key, &c);
+
+        assert_eq!(pkcs1_v15_unpad(&block), Some(msg.to_vec()));
+    }
+}
@@ -0,0 +1,136 @@
+// CBC-MAC: the last block of the CBC encryption of the PKCS#7 padded message
+//
+// It's only secure for fixed length messages under a fixed IV. If the
+// verifier takes the IV from the message the first block can be changed
+// freely, and since the MAC is also the chaining state after the message,
+// two MACs can be combined into one for a concatenated message.
+
+use crate::crypto_helper::{encrypt_cbc, decrypt_ecb, BLOCK_SIZE};
+use crate::ops::xor;
+use crate::pkcs7::pkcs7_pad;
+
+pub const MAC_SIZE: usize = BLOCK_SIZE;
+
+pub fn cbc_mac(key: &[u8], iv: &[u8], msg: &[u8]) -> Vec<u8> {
+    let ct = encrypt_cbc(key, iv, &pkcs7_pad(msg, BLOCK_SIZE));
+
+    ct[ct.len() - BLOCK_SIZE..].to_vec()
+}
+
+pub fn cbc_mac_fixed_iv(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    cbc_mac(key, &[0u8; BLOCK_SIZE], msg)
+}
+
+pub fn verify_cbc_mac(key: &[u8], iv: &[u8], msg: &[u8], mac: &[u8]) -> bool {
+    cbc_mac(key, iv, msg).as_slice() == mac
+}
+
+// Replace the first block of msg with new_first_block and return the IV
+// that keeps the MAC the same. Only works when the verifier uses an IV
+// supplied along with the message.
+pub fn forge_iv(msg: &[u8], iv: &[u8], new_first_block: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    assert!(msg.len() >= BLOCK_SIZE);
+    assert_eq!(new_first_block.len(), BLOCK_SIZE);
+
+    let mut forged_msg = new_first_block.to_vec();
+    forged_msg.extend_from_slice(&msg[BLOCK_SIZE..]);
+
+    let forged_iv = xor(&xor(iv, &msg[..BLOCK_SIZE]), new_first_block);
+
+    (forged_msg, forged_iv)
+}
+
+// Given msg1 with MAC mac1 and msg2 with MAC mac2 under the same key and
+// a zero IV, pad(msg1) || (msg2[0] ^ mac1) || msg2[1..] also has MAC mac2.
+pub fn forge_concatenation(msg1: &[u8], mac1: &[u8], msg2: &[u8]) -> Vec<u8> {
+    assert!(msg2.len() >= BLOCK_SIZE);
+    assert_eq!(mac1.len(), MAC_SIZE);
+
+    let mut forged = pkcs7_pad(msg1, BLOCK_SIZE);
+    forged.extend_from_slice(&xor(&msg2[..BLOCK_SIZE], mac1));
+    forged.extend_from_slice(&msg2[BLOCK_SIZE..]);
+
+    forged
+}
+
+// The block that takes the CBC chain from state `from` to state `to`,
+// i.e. E_K(from ^ glue) = to
+pub fn glue_block(key: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    xor(&decrypt_ecb(key, to), from)
+}
+
+// With the key CBC-MAC is just a (bad) hash function. This builds
+// pad(prefix) || glue || suffix with CBC-MAC target_mac by running the
+// chain backwards through the padded suffix to find the state the glue
+// block has to reach.
+pub fn cbc_mac_collision(key: &[u8], iv: &[u8], target_mac: &[u8], prefix: &[u8], suffix: &[u8]) -> Vec<u8> {
+    assert_eq!(target_mac.len(), MAC_SIZE);
+
+    let padded_suffix = pkcs7_pad(suffix, BLOCK_SIZE);
+
+    let needed_state = padded_suffix.chunks(BLOCK_SIZE)
+        .rev()
+        .fold(target_mac.to_vec(), |state, block| xor(&decrypt_ecb(key, &state), block));
+
+    // the chaining state after pad(prefix) is the MAC of prefix
+    let prefix_state = cbc_mac(key, iv, prefix);
+
+    let mut forged = pkcs7_pad(prefix, BLOCK_SIZE);
+    forged.extend_from_slice(&glue_block(key, &prefix_state, &needed_state));
+    forged.extend_from_slice(suffix);
+
+    forged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_bytes;
+
+    #[test]
+    fn test_forge_iv() {
+        let key = random_bytes(16);
+        let iv = random_bytes(16);
+        let msg = b"from=2&to=2&amount=1000000";
+
+        let mac = cbc_mac(&key, &iv, msg);
+        assert!(verify_cbc_mac(&key, &iv, msg, &mac));
+
+        let (forged_msg, forged_iv) = forge_iv(msg, &iv, b"from=1&to=2&amou");
+        assert_eq!(forged_msg, b"from=1&to=2&amount=1000000");
+        assert!(verify_cbc_mac(&key, &forged_iv, &forged_msg, &mac));
+        assert!(!verify_cbc_mac(&key, &iv, &forged_msg, &mac));
+    }
+
+    #[test]
+    fn test_forge_concatenation() {
+        let key = random_bytes(16);
+        let msg1 = b"from=1&tx_list=3:5;4:10";
+        let msg2 = b"from=2&tx_list=0:0;2:1000000";
+
+        let mac1 = cbc_mac_fixed_iv(&key, msg1);
+        let mac2 = cbc_mac_fixed_iv(&key, msg2);
+
+        let forged = forge_concatenation(msg1, &mac1, msg2);
+        assert!(forged.starts_with(msg1));
+        assert!(forged.ends_with(b";2:1000000"));
+        assert_eq!(cbc_mac_fixed_iv(&key, &forged), mac2);
+    }
+
+    #[test]
+    fn test_cbc_mac_collision() {
+        let key = b"YELLOW SUBMARINE";
+        let iv = [0u8; BLOCK_SIZE];
+
+        let mac = cbc_mac(key, &iv, b"alert('MZA who was that?');\n");
+        assert_eq!(hex::encode(&mac), "296b8d7cb78a243dda4d0a61d33bbdd1");
+
+        for suffix in [&b""[..], b"\n", b"a suffix longer than one block"].iter() {
+            let forged = cbc_mac_collision(key, &iv, &mac, b"alert('Ayo, the Wu is back!');//", suffix);
+
+            assert!(forged.starts_with(b"alert('Ayo, the Wu is back!');//"));
+            assert!(forged.ends_with(suffix));
+            assert_eq!(cbc_mac(key, &iv, &forged), mac);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Compression ratio side channel (CRIME)
+//
+// When attacker controlled data is compressed together with a secret before
+// encryption, the ciphertext gets shorter when the attacker's data repeats
+// part of the secret. Guessing the secret one byte at a time, the right
+// guess extends an LZ77 match instead of adding a literal.
+
+use rand::{self, Rng};
+
+use crate::crypto_helper::{crypt_ctr, encrypt_cbc, BLOCK_SIZE};
+use crate::deflate::deflate;
+use crate::pkcs7::pkcs7_pad;
+use crate::util::random_bytes;
+
+pub const SESSION_ID: &[u8] = b"TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+pub const COOKIE_PREFIX: &[u8] = b"sessionid=";
+
+// Characters a session id can contain, plus the end of the header line
+pub const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=\r";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OracleCipher {
+    Ctr,
+    Cbc,
+}
+
+pub trait CompressionOracle {
+    // length of the compressed and encrypted request carrying payload
+    fn encrypted_len(&self, payload: &[u8]) -> usize;
+}
+
+pub struct SessionOracle {
+    session_id: Vec<u8>,
+    cipher: OracleCipher,
+}
+
+impl SessionOracle {
+    pub fn new(session_id: &[u8], cipher: OracleCipher) -> Self {
+        SessionOracle { session_id: session_id.to_vec(), cipher }
+    }
+}
+
+pub fn format_request(session_id: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut request = Vec::new();
+
+    request.extend_from_slice(b"POST / HTTP/1.1\r\nHost: hapless.com\r\nCookie: sessionid=");
+    request.extend_from_slice(session_id);
+    request.extend_from_slice(format!("\r\nContent-Length: {}\r\n", payload.len()).as_bytes());
+    request.extend_from_slice(payload);
+
+    request
+}
+
+impl CompressionOracle for SessionOracle {
+    // a fresh key and nonce/IV for every request
+    fn encrypted_len(&self, payload: &[u8]) -> usize {
+        let compressed = deflate(&format_request(&self.session_id, payload));
+        let key = random_bytes(16);
+
+        match self.cipher {
+            OracleCipher::Ctr => crypt_ctr(&key, rand::thread_rng().gen(), &compressed).len(),
+            OracleCipher::Cbc => {
+                let iv = random_bytes(BLOCK_SIZE);
+
+                encrypt_cbc(&key, &iv, &pkcs7_pad(&compressed, BLOCK_SIZE)).len()
+            },
+        }
+    }
+}
+
+// Bytes that don't occur in the request so they can't be part of a match.
+// The fixed Huffman code spends 8 bits on the low ones and 9 bits on the
+// high ones, so mixing them shifts the compressed length a bit at a time.
+const JUNK_LOW: &[u8] = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0b\x0c\x0e\x0f\
+    \x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f!#$%&()*,;<>?@[]^_`{|}~";
+
+fn junk(step: usize) -> Vec<u8> {
+    let mut junk: Vec<u8> = (0x90..0x90 + step % 8).map(|b| b as u8).collect();
+    junk.extend_from_slice(&JUNK_LOW[..step / 8]);
+
+    junk
+}
+
+// The guesses that give the shortest ciphertext. The right guess saves
+// less than a byte, which a stream cipher only shows if it crosses a byte
+// boundary and a block cipher only if it crosses a block boundary. Shift
+// the compressed length with incompressible junk until exactly one guess
+// stands out.
+fn best_guesses<O: CompressionOracle>(oracle: &O, known: &[u8], candidates: &[u8]) -> Vec<u8> {
+    let mut best = candidates.to_vec();
+
+    for step in 0..8 * (BLOCK_SIZE + 2) {
+        let lens: Vec<_> = candidates.iter()
+            .map(|c| {
+                let mut payload = junk(step);
+                payload.extend_from_slice(known);
+                payload.push(*c);
+
+                oracle.encrypted_len(&payload)
+            })
+            .collect();
+
+        let min_len = *lens.iter().min().unwrap();
+        let winners: Vec<_> = candidates.iter()
+            .zip(lens.iter())
+            .filter(|(_, len)| **len == min_len)
+            .map(|(c, _)| *c)
+            .collect();
+
+        if winners.len() < best.len() {
+            best = winners;
+        }
+
+        if best.len() == 1 {
+            break;
+        }
+    }
+
+    best
+}
+
+// Recover the bytes following known_prefix in the request one at a time
+// until the terminator is guessed
+pub fn recover_secret<O: CompressionOracle>(
+    oracle: &O,
+    known_prefix: &[u8],
+    alphabet: &[u8],
+    terminator: u8,
+    max_len: usize) -> Vec<u8> {
+    let mut known = known_prefix.to_vec();
+
+    while known.len() - known_prefix.len() < max_len {
+        let mut guesses = best_guesses(oracle, &known, alphabet);
+
+        // Still ambiguous, so look one byte further ahead. Each key costs
+        // oracle queries, so only compute it once per guess.
+        if guesses.len() > 1 {
+            let mut lookahead = known.clone();
+            lookahead.push(0);
+
+            guesses.sort_by_cached_key(|c| {
+                *lookahead.last_mut().unwrap() = *c;
+
+                let next = best_guesses(oracle, &lookahead, alphabet)[0];
+                let mut payload = lookahead.clone();
+                payload.push(next);
+
+                oracle.encrypted_len(&payload)
+            });
+        }
+
+        if guesses[0] == terminator {
+            break;
+        }
+
+        known.push(guesses[0]);
+    }
+
+    known[known_prefix.len()..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::inflate;
+
+    #[test]
+    fn test_oracle_compresses() {
+        let request = format_request(SESSION_ID, b"sessionid=TmV2");
+        assert_eq!(inflate(&deflate(&request)).unwrap(), request);
+
+        let oracle = SessionOracle::new(SESSION_ID, OracleCipher::Ctr);
+        assert!(oracle.encrypted_len(b"sessionid=TmV2") < oracle.encrypted_len(b"sessionid=XYZW"));
+    }
+
+    #[test]
+    fn test_recover_ctr() {
+        let oracle = SessionOracle::new(SESSION_ID, OracleCipher::Ctr);
+
+        let recovered = recover_secret(&oracle, COOKIE_PREFIX, BASE64_ALPHABET, b'\r', 64);
+        assert_eq!(recovered, SESSION_ID);
+    }
+
+    #[test]
+    fn test_recover_cbc() {
+        let oracle = SessionOracle::new(b"c2VjcmV0IHNlc3Npb24=", OracleCipher::Cbc);
+
+        let recovered = recover_secret(&oracle, COOKIE_PREFIX, BASE64_ALPHABET, b'\r', 64);
+        assert_eq!(recovered, b"c2VjcmV0IHNlc3Npb24=");
+    }
+}
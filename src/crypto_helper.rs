@@ -6,11 +6,9 @@ use crypto::blockmodes::NoPadding;
 use crypto::buffer::{RefReadBuffer, RefWriteBuffer, ReadBuffer, WriteBuffer};
 use crypto::aessafe::AesSafe128Encryptor;
 use crypto::symmetriccipher::BlockEncryptor;
-use crypto::hmac::Hmac;
-use crypto::sha2::Sha256;
-use crypto::digest::Digest;
-use crypto::mac::Mac;
 use byteorder::{LittleEndian, WriteBytesExt};
+use crate::hmac::hmac;
+use crate::sha256::{Sha256, sha256_digest};
 
 pub const BLOCK_SIZE: usize = 16;
 
@@ -96,19 +94,9 @@ pub fn crypt_ctr(key: &[u8], nonce: u64, input: &[u8]) -> Vec<u8> {
 }
 
 pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
-    let mut hmac = Hmac::new(Sha256::new(), key);
-    hmac.input(data);
-    let mut output_buf = vec![0u8; hmac.output_bytes()];
-    hmac.raw_result(&mut output_buf);
-
-    output_buf
+    hmac::<Sha256>(key, data)
 }
 
 pub fn sha256(data: &[u8]) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    hasher.input(data);
-    let mut output_buf = vec![0u8; hasher.output_bytes()];
-    hasher.result(&mut output_buf);
-
-    output_buf
-}
\ No newline at end of file
+    sha256_digest(data)
+}
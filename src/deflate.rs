@@ -0,0 +1,319 @@
+// Minimal DEFLATE (https://tools.ietf.org/html/rfc1951) compressor: greedy
+// LZ77 matching encoded with the fixed Huffman codes in a single block.
+// It doesn't compress as well as zlib but the output is valid raw DEFLATE.
+// The decompressor only understands stored and fixed Huffman blocks.
+
+use std::collections::HashMap;
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// how many earlier positions with the same 3 byte prefix to check
+const MAX_CHAIN: usize = 256;
+
+const END_OF_BLOCK: u16 = 256;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Literal(u8),
+    Match { length: usize, distance: usize },
+}
+
+fn lz77(data: &[u8]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chains: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let mut best = (0, 0);
+
+        if pos + MIN_MATCH <= data.len() {
+            if let Some(candidates) = chains.get(&data[pos..pos + MIN_MATCH]) {
+                for start in candidates.iter().rev().take(MAX_CHAIN) {
+                    if pos - start > WINDOW_SIZE {
+                        break;
+                    }
+
+                    let length = data[*start..].iter()
+                        .zip(data[pos..].iter())
+                        .take(MAX_MATCH)
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if length > best.0 {
+                        best = (length, pos - start);
+                    }
+                }
+            }
+        }
+
+        let advance = if best.0 >= MIN_MATCH {
+            tokens.push(Token::Match { length: best.0, distance: best.1 });
+            best.0
+        } else {
+            tokens.push(Token::Literal(data[pos]));
+            1
+        };
+
+        for i in pos..pos + advance {
+            if i + MIN_MATCH <= data.len() {
+                chains.entry(&data[i..i + MIN_MATCH]).or_insert_with(Vec::new).push(i);
+            }
+        }
+
+        pos += advance;
+    }
+
+    tokens
+}
+
+// DEFLATE packs bits starting from the least significant bit of each byte
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter { out: Vec::new(), acc: 0, bits: 0 }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.bits;
+        self.bits += count;
+
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    // Huffman codes are stored most significant bit first
+    fn write_code(&mut self, code: u32, len: u32) {
+        let reversed = (0..len).fold(0, |acc, i| (acc << 1) | ((code >> i) & 1));
+
+        self.write_bits(reversed, len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+        }
+
+        self.out
+    }
+}
+
+fn write_lit_len(writer: &mut BitWriter, symbol: u16) {
+    let symbol = u32::from(symbol);
+
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let li = LENGTH_BASE.iter().rposition(|base| *base as usize <= length).unwrap();
+    write_lit_len(writer, 257 + li as u16);
+    writer.write_bits((length - LENGTH_BASE[li] as usize) as u32, u32::from(LENGTH_EXTRA[li]));
+
+    let di = DIST_BASE.iter().rposition(|base| *base as usize <= distance).unwrap();
+    writer.write_code(di as u32, 5);
+    writer.write_bits((distance - DIST_BASE[di] as usize) as u32, u32::from(DIST_EXTRA[di]));
+}
+
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman codes)
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    for token in lz77(data) {
+        match token {
+            Token::Literal(b) => write_lit_len(&mut writer, u16::from(b)),
+            Token::Match { length, distance } => write_match(&mut writer, length, distance),
+        }
+    }
+
+    write_lit_len(&mut writer, END_OF_BLOCK);
+
+    writer.finish()
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn read_bit(&mut self) -> Option<u32> {
+        let byte = self.data.get(self.pos / 8)?;
+        let bit = (byte >> (self.pos % 8)) & 1;
+        self.pos += 1;
+
+        Some(u32::from(bit))
+    }
+
+    fn read_bits(&mut self, count: u32) -> Option<u32> {
+        let mut value = 0;
+
+        for i in 0..count {
+            value |= self.read_bit()? << i;
+        }
+
+        Some(value)
+    }
+
+    fn read_code(&mut self, len: u32) -> Option<u32> {
+        let mut code = 0;
+
+        for _ in 0..len {
+            code = (code << 1) | self.read_bit()?;
+        }
+
+        Some(code)
+    }
+
+    fn align(&mut self) {
+        self.pos = (self.pos + 7) / 8 * 8;
+    }
+}
+
+fn read_lit_len(reader: &mut BitReader) -> Option<u16> {
+    let mut code = reader.read_code(7)?;
+
+    if code <= 0x17 {
+        return Some(256 + code as u16);
+    }
+
+    code = (code << 1) | reader.read_bit()?;
+
+    match code {
+        0x30..=0xbf => return Some((code - 0x30) as u16),
+        0xc0..=0xc7 => return Some((280 + code - 0xc0) as u16),
+        _ => (),
+    }
+
+    code = (code << 1) | reader.read_bit()?;
+
+    match code {
+        0x190..=0x1ff => Some((144 + code - 0x190) as u16),
+        _ => None,
+    }
+}
+
+fn inflate_fixed_block(reader: &mut BitReader, out: &mut Vec<u8>) -> Option<()> {
+    loop {
+        let symbol = read_lit_len(reader)?;
+
+        if symbol < END_OF_BLOCK {
+            out.push(symbol as u8);
+            continue;
+        }
+
+        if symbol == END_OF_BLOCK {
+            return Some(());
+        }
+
+        let li = (symbol - 257) as usize;
+        let length = *LENGTH_BASE.get(li)? as usize + reader.read_bits(u32::from(LENGTH_EXTRA[li]))? as usize;
+
+        let di = reader.read_code(5)? as usize;
+        let distance = *DIST_BASE.get(di)? as usize + reader.read_bits(u32::from(DIST_EXTRA[di]))? as usize;
+
+        if distance > out.len() {
+            return None;
+        }
+
+        // the match can overlap the bytes it produces
+        for _ in 0..length {
+            let b = out[out.len() - distance];
+            out.push(b);
+        }
+    }
+}
+
+pub fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = BitReader { data, pos: 0 };
+    let mut out = Vec::new();
+
+    loop {
+        let last = reader.read_bits(1)?;
+
+        match reader.read_bits(2)? {
+            0 => {
+                reader.align();
+
+                let len = reader.read_bits(16)? as usize;
+                let nlen = reader.read_bits(16)? as usize;
+
+                if len != !nlen & 0xffff {
+                    return None;
+                }
+
+                let start = reader.pos / 8;
+                out.extend_from_slice(data.get(start..start + len)?);
+                reader.pos += len * 8;
+            },
+            1 => inflate_fixed_block(&mut reader, &mut out)?,
+            _ => return None,
+        }
+
+        if last == 1 {
+            return Some(out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_bytes;
+
+    #[test]
+    fn test_roundtrip() {
+        let text = b"Cookie: sessionid=TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=\r\nsessionid=TmV2";
+        let runs = [0x61u8; 1000];
+        let random = random_bytes(5000);
+
+        for data in [&b""[..], text, &runs, &random].iter() {
+            assert_eq!(inflate(&deflate(data)).as_ref().map(|v| v.as_slice()), Some(*data));
+        }
+
+        assert!(deflate(&runs).len() < 20);
+        assert!(deflate(text).len() < text.len());
+    }
+
+    #[test]
+    fn test_inflate() {
+        // raw DEFLATE output of zlib for "hello hello hello"
+        let compressed = hex::decode("cb48cdc9c957c8409000").unwrap();
+        assert_eq!(inflate(&compressed).unwrap(), b"hello hello hello");
+
+        // a stored block
+        assert_eq!(inflate(&[0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c']).unwrap(), b"abc");
+    }
+}
@@ -6,6 +6,59 @@ lazy_static! {
         16).unwrap();
 
     pub static ref G: Int = Int::from(2);
+
+    // Group from challenge 57: g generates a subgroup of prime order q and
+    // (p - 1) / q has plenty of small factors
+    pub static ref CH57_GROUP: DhGroup = DhGroup {
+        p: Int::from_str_radix("7199773997391911030609999317773941274322764333428698921736339643928346453700085358802973900485592910475480089726140708102474957429903531369589969318716771", 10).unwrap(),
+        g: Int::from_str_radix("4565356397095740655436854503483826832136106141639563487732438195343690437606117828318042418238184896212352329118608100083187535033402010599512641674644143", 10).unwrap(),
+        q: Int::from_str_radix("236234353446506858198510045061214171961", 10).unwrap(),
+    };
+
+    // Group from challenge 58: the small factors of (p - 1) / q don't
+    // cover all of q
+    pub static ref CH58_GROUP: DhGroup = DhGroup {
+        p: Int::from_str_radix("11470374874925275658116663507232161402086650258453896274534991676898999262641581519101074740642369848233294239851519212341844337347119899874391456329785623", 10).unwrap(),
+        g: Int::from_str_radix("622952335333961296978159266084741085889881358738459939978290179936063635566740258555167783009058567397963466103140082647486611657350811560630587013183357", 10).unwrap(),
+        q: Int::from_str_radix("335062023296420808191071248367701059461", 10).unwrap(),
+    };
+}
+
+// A group with a generator g of prime order q in Z_p*
+#[derive(Debug, Clone)]
+pub struct DhGroup {
+    pub p: Int,
+    pub g: Int,
+    pub q: Int,
+}
+
+impl DhGroup {
+    // (public key, private key) with the private key in [1, q)
+    pub fn gen_pair(&self) -> (Int, Int) {
+        let priv_key = rand::thread_rng().gen_int_range(&Int::from(1), &self.q);
+        let pub_key = self.g.pow_mod(&priv_key, &self.p);
+
+        (pub_key, priv_key)
+    }
+
+    // j = (p - 1) / q
+    pub fn cofactor(&self) -> Int {
+        (&self.p - 1) / &self.q
+    }
+
+    // A random element of order r, r being a prime factor of p - 1
+    pub fn element_of_order(&self, r: &Int) -> Int {
+        let exp = (&self.p - 1) / r;
+        let mut rng = rand::thread_rng();
+
+        loop {
+            let h = rng.gen_int_range(&Int::from(2), &self.p).pow_mod(&exp, &self.p);
+
+            if h != 1 {
+                return h;
+            }
+        }
+    }
 }
 
 pub fn gen_dh_pair(p: &Int, g: &Int) -> (Int, Int) {
@@ -33,4 +86,23 @@ mod tests {
 
         assert_eq!(s_a, s_b);
     }
+
+    #[test]
+    fn test_group() {
+        let group = &*super::CH57_GROUP;
+
+        assert_eq!(group.g.pow_mod(&group.q, &group.p), 1);
+        assert_eq!((&group.p - 1) % &group.q, 0);
+
+        let (pub_a, priv_a) = group.gen_pair();
+        let (pub_b, priv_b) = group.gen_pair();
+
+        assert_eq!(super::gen_session_key(&pub_b, &priv_a, &group.p), super::gen_session_key(&pub_a, &priv_b, &group.p));
+
+        let r = ramp::int::Int::from(7963);
+        let h = group.element_of_order(&r);
+
+        assert!(h != 1);
+        assert_eq!(h.pow_mod(&r, &group.p), 1);
+    }
 }
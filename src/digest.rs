@@ -0,0 +1,56 @@
+// Common interface for the hash functions implemented in this crate so
+// code like HMAC and RSA signatures can be written once for all of them
+
+pub trait Digest: Sized {
+    const BLOCK_SIZE: usize;
+    const OUTPUT_SIZE: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(data);
+
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Digest;
+    use crate::md4::{Md4, md4_digest};
+    use crate::sha1::{Sha1, sha1_digest};
+
+    fn check_streaming<D: Digest>(one_shot: fn(&[u8]) -> Vec<u8>) {
+        let data: Vec<u8> = (0..1000u32).map(|x| (x * 7) as u8).collect();
+
+        for chunk_size in [1, 3, 64, 100, 1000].iter() {
+            let mut hasher = D::new();
+
+            for chunk in data.chunks(*chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), one_shot(&data));
+        }
+    }
+
+    #[test]
+    fn test_streaming() {
+        check_streaming::<Sha1>(sha1_digest);
+        check_streaming::<Md4>(md4_digest);
+    }
+
+    #[test]
+    fn test_with_state() {
+        let mut hasher = Sha1::with_state(
+            [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0], 0);
+        hasher.update(b"abc");
+
+        assert_eq!(hasher.finalize(), sha1_digest(b"abc"));
+    }
+}
@@ -0,0 +1,252 @@
+// Duplicate signature key selection
+// (Blake-Wilson and Menezes, "Unknown Key-Share Attacks on the
+// Station-to-Station (STS) Protocol")
+//
+// A signature doesn't bind the key that made it: given someone else's
+// signature we can often make a key pair of our own that it verifies under.
+// For ECDSA we get to pick the generator, for RSA the modulus and exponent.
+
+use ramp::{Int, RandomInt};
+use rand::{self, Rng};
+
+use crate::dsa::DSASignature;
+use crate::ecdsa::{ECDSAPubKey, ECDSAPrivKey, hash_msg_ecdsa};
+use crate::md4::Md4;
+use crate::ops::{IntOpsExt, crt};
+use crate::prime::{small_primes, test_prime};
+use crate::rsa::{RSAPubKey, RSAPrivKey, SigDigest, pkcs1_v15_pad_sign, rsa_pair_from_primes};
+
+// Verification computes R = u1 * G + u2 * Q and compares its x to r. Keep R
+// and pick a new private key d', then G' = (u1 + u2 * d')^-1 * R and
+// Q' = d' * G' give the same R.
+pub fn ecdsa_dsks(key: &ECDSAPubKey, msg: &[u8], sig: &DSASignature) -> (ECDSAPubKey, ECDSAPrivKey) {
+    let curve = &key.curve;
+    let n = &curve.order;
+
+    let w = sig.s.inv_mod(n).unwrap();
+    let u1 = (hash_msg_ecdsa(msg, n) * &w) % n;
+    let u2 = (&sig.r * &w) % n;
+
+    let r_point = curve.add(&curve.scalar_mul_base(&u1), &curve.scalar_mul(&key.q, &u2));
+
+    loop {
+        let d = rand::thread_rng().gen_int_range(&Int::from(1), n);
+        let t = (&u1 + &u2 * &d) % n;
+
+        let t_inv = match t.inv_mod(n) {
+            Some(t_inv) => t_inv,
+            None => continue,
+        };
+
+        let new_curve = curve.with_base(curve.scalar_mul(&r_point, &t_inv), n.clone());
+        let q = new_curve.scalar_mul_base(&d);
+
+        return (ECDSAPubKey { curve: new_curve.clone(), q }, ECDSAPrivKey { curve: new_curve, d });
+    }
+}
+
+// Primes below this make up p - 1 for the smooth primes. Bigger keys
+// double it until there are enough primes.
+const SMOOTH_BOUND: u64 = 1 << 12;
+
+// log2 of the product of the pool
+fn pool_bits(pool: &[u64]) -> f64 {
+    pool.iter().map(|r| (*r as f64).log2()).sum()
+}
+
+// Odd primes for p - 1 and q - 1 with bits each. Leave plenty over so
+// there's still a choice left for q after p's factors are gone.
+fn smooth_pool(bits: u32) -> Vec<u64> {
+    let mut bound = SMOOTH_BOUND;
+
+    loop {
+        let pool: Vec<u64> = small_primes(bound).into_iter().skip(1).collect();
+
+        if pool_bits(&pool) >= 4.0 * f64::from(bits) {
+            return pool;
+        }
+
+        bound *= 2;
+    }
+}
+
+// A prime p >= 2^(bits - 1) with p - 1 = 2 * (distinct primes from pool).
+// Returns p and the factors of p - 1, or None if the whole pool isn't
+// enough to make a number that big.
+fn gen_smooth_prime(bits: u32, pool: &[u64]) -> Option<(Int, Vec<u64>)> {
+    if pool_bits(pool) + 1.0 < f64::from(bits) {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+
+    loop {
+        let mut factors = vec![2];
+        let mut p_minus_1 = Int::from(2);
+
+        while p_minus_1.bit_length() < bits {
+            let r = pool[rng.gen_range(0, pool.len())];
+
+            if !factors.contains(&r) {
+                factors.push(r);
+                p_minus_1 *= Int::from(r);
+            }
+        }
+
+        let p = p_minus_1 + 1;
+
+        if test_prime(&p, 40) {
+            return Some((p, factors));
+        }
+    }
+}
+
+// g generates Z_p* if g^((p - 1) / r) != 1 for every prime r dividing p - 1
+fn is_generator(g: &Int, p: &Int, factors: &[u64]) -> bool {
+    let p_minus_1 = p - Int::from(1);
+
+    factors.iter().all(|r| g.pow_mod(&(&p_minus_1 / Int::from(*r)), p) != 1)
+}
+
+// Discrete log of y to the base g mod a prime p where p - 1 is the product
+// of the distinct small primes in factors. Each factor gives x mod r by
+// brute force in the subgroup of order r, and the CRT puts them together.
+pub fn pohlig_hellman(g: &Int, y: &Int, p: &Int, factors: &[u64]) -> Option<Int> {
+    let p_minus_1 = p - Int::from(1);
+    let mut residues = Vec::with_capacity(factors.len());
+
+    for r in factors.iter() {
+        let exp = &p_minus_1 / Int::from(*r);
+        let g_r = g.pow_mod(&exp, p);
+        let y_r = y.pow_mod(&exp, p);
+
+        let mut acc = Int::from(1);
+        let x = (0..*r).find(|_| {
+            let found = acc == y_r;
+            acc = (&acc * &g_r) % p;
+            found
+        })?;
+
+        residues.push((Int::from(x), Int::from(*r)));
+    }
+
+    Some(crt(&residues).0)
+}
+
+// Make an RSA key pair the signature verifies under: pick primes p and q
+// with smooth p - 1 and q - 1 such that both s and pad(m) generate Z_p* and
+// Z_q*, then e' = log_s(pad(m)) mod (p - 1)(q - 1) with Pohlig-Hellman.
+// pad(m) being a generator makes e' coprime to (p - 1)(q - 1) so there is a
+// matching private key too.
+pub fn rsa_dsks_with<D: SigDigest>(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> (RSAPubKey, RSAPrivKey) {
+    let k = (key.n.bit_length() as usize + 7) / 8;
+    let pad = Int::from_bytes(&pkcs1_v15_pad_sign::<D>(msg, k));
+    let s = Int::from_bytes(sig);
+
+    // n' ends up bigger than n so pad(m) and s fit
+    let bits = key.n.bit_length() / 2 + 1;
+    let mut pool = smooth_pool(bits);
+
+    // The factors used for p are taken out of the pool so q - 1 only
+    // shares the 2 with p - 1
+    let mut gen_prime = || loop {
+        let (p, factors) = gen_smooth_prime(bits, &pool).expect("smooth prime pool is too small");
+
+        if is_generator(&s, &p, &factors) && is_generator(&pad, &p, &factors) {
+            pool.retain(|r| !factors.contains(r));
+
+            return (p, factors);
+        }
+    };
+
+    let (p, p_factors) = gen_prime();
+    let (q, q_factors) = gen_prime();
+
+    let e_p = pohlig_hellman(&s, &pad, &p, &p_factors).unwrap();
+    let e_q = pohlig_hellman(&s, &pad, &q, &q_factors).unwrap();
+
+    // Both are odd, the rest of the factors are distinct
+    let residues: Vec<_> = p_factors.iter().map(|r| (&e_p, r))
+        .chain(q_factors.iter().skip(1).map(|r| (&e_q, r)))
+        .map(|(e, r)| (e % Int::from(*r), Int::from(*r)))
+        .collect();
+
+    let (e, _) = crt(&residues);
+
+    rsa_pair_from_primes(p, q, e).unwrap()
+}
+
+pub fn rsa_dsks(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> (RSAPubKey, RSAPrivKey) {
+    rsa_dsks_with::<Md4>(key, msg, sig)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::SET8_CURVE;
+    use crate::ecdsa::{gen_ecdsa_pair, sign_ecdsa, verify_ecdsa};
+    use crate::rsa::{gen_rsa_pair, sign_rsa, verify_rsa, decrypt_rsa};
+
+    #[test]
+    fn test_ecdsa_dsks() {
+        let (pub_key, priv_key) = gen_ecdsa_pair(&SET8_CURVE);
+        let msg = b"hi mom";
+        let sig = sign_ecdsa(&priv_key, msg);
+
+        let (new_pub, new_priv) = ecdsa_dsks(&pub_key, msg, &sig);
+
+        assert!(new_pub.q != pub_key.q);
+        assert_eq!(new_pub.curve.scalar_mul_base(&new_priv.d), new_pub.q);
+        assert!(verify_ecdsa(&new_pub, msg, &sig));
+        assert!(!verify_ecdsa(&new_pub, b"hi dad", &sig));
+    }
+
+    #[test]
+    fn test_pohlig_hellman() {
+        // 2 * 3 * 5 * 7 * 11 + 1
+        let p = Int::from(2311);
+        let factors = [2, 3, 5, 7, 11];
+        let g = Int::from(3);
+
+        assert!(is_generator(&g, &p, &factors));
+
+        for x in [0, 1, 1234, 2309].iter() {
+            let y = g.pow_mod(&Int::from(*x), &p);
+
+            assert_eq!(pohlig_hellman(&g, &y, &p, &factors), Some(Int::from(*x)));
+        }
+    }
+
+    #[test]
+    fn test_smooth_pool() {
+        // 2^12 covers the smaller keys, 8192 bit keys need more
+        assert_eq!(smooth_pool(512).last(), small_primes(SMOOTH_BOUND).last());
+
+        for bits in [512, 4097, 8193].iter() {
+            assert!(pool_bits(&smooth_pool(*bits)) >= 4.0 * f64::from(*bits));
+        }
+
+        // Too few primes fails instead of looping
+        assert!(gen_smooth_prime(64, &[3, 5, 7]).is_none());
+
+        let (p, factors) = gen_smooth_prime(64, &smooth_pool(64)).unwrap();
+        assert!(p.bit_length() >= 64);
+        assert_eq!(factors.iter().fold(Int::from(1), |acc, r| acc * Int::from(*r)), p - Int::from(1));
+    }
+
+    #[test]
+    fn test_rsa_dsks() {
+        let (pub_key, priv_key) = gen_rsa_pair(512);
+        let msg = b"hi mom";
+        let sig = sign_rsa(&priv_key, msg);
+
+        let (new_pub, new_priv) = rsa_dsks(&pub_key, msg, &sig);
+
+        assert!(new_pub.n > pub_key.n);
+        verify_rsa(&new_pub, msg, &sig).unwrap();
+
+        // the new private key makes the same signature from the same block
+        let pad = Int::from_bytes(&pkcs1_v15_pad_sign::<Md4>(msg, 64));
+        assert_eq!(decrypt_rsa(&new_priv, &pad).unwrap(), Int::from_bytes(&sig));
+    }
+}
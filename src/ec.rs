@@ -0,0 +1,357 @@
+// Elliptic curves in short Weierstrass form: y^2 = x^3 + ax + b (mod p)
+// Projective formulas use Jacobian coordinates (x = X/Z^2, y = Y/Z^3), see
+// https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html
+
+use ramp::int::{Int, RandomInt};
+use rand;
+
+use crate::ops::IntOpsExt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ECPoint {
+    Infinity,
+    Point { x: Int, y: Int },
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectivePoint {
+    pub x: Int,
+    pub y: Int,
+    pub z: Int,
+}
+
+#[derive(Debug, Clone)]
+pub struct ECCurve {
+    pub a: Int,
+    pub b: Int,
+    pub p: Int,
+    // order of the subgroup generated by base
+    pub order: Int,
+    pub base: ECPoint,
+}
+
+lazy_static! {
+    // Curve from set 8 of the challenges
+    pub static ref SET8_CURVE: ECCurve = ECCurve {
+        a: Int::from(-95051),
+        b: Int::from(11_279_326),
+        p: Int::from_str_radix("233970423115425145524320034830162017933", 10).unwrap(),
+        order: Int::from_str_radix("29246302889428143187362802287225875743", 10).unwrap(),
+        base: ECPoint::Point {
+            x: Int::from(182),
+            y: Int::from_str_radix("85518893674295321206118380980485522083", 10).unwrap(),
+        },
+    };
+}
+
+impl ECPoint {
+    pub fn new(x: Int, y: Int) -> Self {
+        ECPoint::Point { x, y }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        *self == ECPoint::Infinity
+    }
+
+    pub fn x(&self) -> Option<&Int> {
+        match self {
+            ECPoint::Infinity => None,
+            ECPoint::Point { x, .. } => Some(x),
+        }
+    }
+}
+
+impl ProjectivePoint {
+    pub fn infinity() -> Self {
+        ProjectivePoint { x: Int::from(1), y: Int::from(1), z: Int::from(0) }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z == 0
+    }
+}
+
+impl ECCurve {
+    pub fn with_base(&self, base: ECPoint, order: Int) -> ECCurve {
+        ECCurve { base, order, ..self.clone() }
+    }
+
+    // A random point other than infinity
+    pub fn random_point(&self) -> ECPoint {
+        let mut rng = rand::thread_rng();
+
+        loop {
+            let x = rng.gen_uint_below(&self.p);
+            let rhs = (x.pow(3) + &self.a * &x + &self.b).real_mod(&self.p);
+
+            if let Some(y) = rhs.sqrt_mod(&self.p) {
+                return ECPoint::Point { x, y };
+            }
+        }
+    }
+
+    // A point of order r given the order of the whole curve, r being prime.
+    // The r-part of the group isn't necessarily cyclic, so strip all the
+    // factors of r from the order and then multiply by r until the next
+    // multiple would be infinity.
+    pub fn point_of_order(&self, curve_order: &Int, r: &Int) -> ECPoint {
+        let mut cofactor = curve_order.clone();
+        while &cofactor % r == 0 {
+            cofactor /= r;
+        }
+
+        loop {
+            let mut pt = self.scalar_mul(&self.random_point(), &cofactor);
+
+            if pt.is_infinity() {
+                continue;
+            }
+
+            loop {
+                let next = self.scalar_mul(&pt, r);
+
+                if next.is_infinity() {
+                    return pt;
+                }
+
+                pt = next;
+            }
+        }
+    }
+
+    pub fn is_on_curve(&self, pt: &ECPoint) -> bool {
+        match pt {
+            ECPoint::Infinity => true,
+            ECPoint::Point { x, y } => {
+                if x < &0 || x >= &self.p || y < &0 || y >= &self.p {
+                    return false;
+                }
+
+                let lhs = y.pow_mod(&Int::from(2), &self.p);
+                let rhs = (x.pow(3) + &self.a * x + &self.b).real_mod(&self.p);
+
+                lhs == rhs
+            }
+        }
+    }
+
+    // Checks that a point received from someone else is on the curve and
+    // in the subgroup generated by the base point
+    pub fn validate_point(&self, pt: &ECPoint) -> bool {
+        !pt.is_infinity() && self.is_on_curve(pt) && self.scalar_mul(pt, &self.order).is_infinity()
+    }
+
+    pub fn negate(&self, pt: &ECPoint) -> ECPoint {
+        match pt {
+            ECPoint::Infinity => ECPoint::Infinity,
+            ECPoint::Point { x, y } => ECPoint::Point { x: x.clone(), y: (-y.clone()).real_mod(&self.p) },
+        }
+    }
+
+    pub fn add(&self, p1: &ECPoint, p2: &ECPoint) -> ECPoint {
+        let (x1, y1, x2, y2) = match (p1, p2) {
+            (ECPoint::Infinity, _) => return p2.clone(),
+            (_, ECPoint::Infinity) => return p1.clone(),
+            (ECPoint::Point { x: x1, y: y1 }, ECPoint::Point { x: x2, y: y2 }) => (x1, y1, x2, y2),
+        };
+
+        let p = &self.p;
+
+        if x1 == x2 {
+            if (y1 + y2).real_mod(p) == 0 {
+                return ECPoint::Infinity;
+            }
+
+            return self.double(p1);
+        }
+
+        let m = ((y2 - y1) * (x2 - x1).real_mod(p).inv_mod(p).unwrap()).real_mod(p);
+        let x3 = (&m * &m - x1 - x2).real_mod(p);
+        let y3 = (m * (x1 - &x3) - y1).real_mod(p);
+
+        ECPoint::Point { x: x3, y: y3 }
+    }
+
+    pub fn double(&self, pt: &ECPoint) -> ECPoint {
+        let (x, y) = match pt {
+            ECPoint::Infinity => return ECPoint::Infinity,
+            ECPoint::Point { x, y } => (x, y),
+        };
+
+        let p = &self.p;
+
+        if y == &0 {
+            return ECPoint::Infinity;
+        }
+
+        let m = ((x * x * Int::from(3) + &self.a) * (y * Int::from(2)).inv_mod(p).unwrap()).real_mod(p);
+        let x3 = (&m * &m - x * Int::from(2)).real_mod(p);
+        let y3 = (m * (x - &x3) - y).real_mod(p);
+
+        ECPoint::Point { x: x3, y: y3 }
+    }
+
+    pub fn to_projective(&self, pt: &ECPoint) -> ProjectivePoint {
+        match pt {
+            ECPoint::Infinity => ProjectivePoint::infinity(),
+            ECPoint::Point { x, y } => ProjectivePoint { x: x.clone(), y: y.clone(), z: Int::from(1) },
+        }
+    }
+
+    pub fn to_affine(&self, pt: &ProjectivePoint) -> ECPoint {
+        if pt.is_infinity() {
+            return ECPoint::Infinity;
+        }
+
+        let p = &self.p;
+        let z_inv = pt.z.inv_mod(p).unwrap();
+        let z_inv2 = (&z_inv * &z_inv) % p;
+        let z_inv3 = (&z_inv2 * &z_inv) % p;
+
+        ECPoint::Point {
+            x: (&pt.x * z_inv2).real_mod(p),
+            y: (&pt.y * z_inv3).real_mod(p),
+        }
+    }
+
+    pub fn double_projective(&self, pt: &ProjectivePoint) -> ProjectivePoint {
+        if pt.is_infinity() || pt.y == 0 {
+            return ProjectivePoint::infinity();
+        }
+
+        let p = &self.p;
+        let (x, y, z) = (&pt.x, &pt.y, &pt.z);
+
+        let y2 = (y * y) % p;
+        let z2 = (z * z) % p;
+        let s = (x * &y2 * Int::from(4)) % p;
+        let m = (x * x * Int::from(3) + &self.a * &z2 * &z2) % p;
+
+        let x3 = (&m * &m - &s * Int::from(2)).real_mod(p);
+        let y3 = (m * (s - &x3) - &y2 * &y2 * Int::from(8)).real_mod(p);
+        let z3 = (y * z * Int::from(2)).real_mod(p);
+
+        ProjectivePoint { x: x3, y: y3, z: z3 }
+    }
+
+    pub fn add_projective(&self, p1: &ProjectivePoint, p2: &ProjectivePoint) -> ProjectivePoint {
+        if p1.is_infinity() {
+            return p2.clone();
+        }
+
+        if p2.is_infinity() {
+            return p1.clone();
+        }
+
+        let p = &self.p;
+
+        let z1_2 = (&p1.z * &p1.z) % p;
+        let z2_2 = (&p2.z * &p2.z) % p;
+        let u1 = (&p1.x * &z2_2).real_mod(p);
+        let u2 = (&p2.x * &z1_2).real_mod(p);
+        let s1 = (&p1.y * &z2_2 * &p2.z).real_mod(p);
+        let s2 = (&p2.y * &z1_2 * &p1.z).real_mod(p);
+
+        if u1 == u2 {
+            if s1 != s2 {
+                return ProjectivePoint::infinity();
+            }
+
+            return self.double_projective(p1);
+        }
+
+        let h = &u2 - &u1;
+        let r = &s2 - &s1;
+        let h2 = (&h * &h) % p;
+        let h3 = (&h2 * &h) % p;
+        let u1h2 = (&u1 * &h2) % p;
+
+        let x3 = (&r * &r - &h3 - &u1h2 * Int::from(2)).real_mod(p);
+        let y3 = (r * (u1h2 - &x3) - s1 * h3).real_mod(p);
+        let z3 = (h * &p1.z * &p2.z).real_mod(p);
+
+        ProjectivePoint { x: x3, y: y3, z: z3 }
+    }
+
+    // Double and add in projective coordinates, negative scalars multiply
+    // the negated point
+    pub fn scalar_mul(&self, pt: &ECPoint, k: &Int) -> ECPoint {
+        let (pt, k) = if k < &0 {
+            (self.negate(pt), -k.clone())
+        } else {
+            (pt.clone(), k.clone())
+        };
+
+        let pt = self.to_projective(&pt);
+        let mut result = ProjectivePoint::infinity();
+
+        for bit in (0..k.bit_length()).rev() {
+            result = self.double_projective(&result);
+
+            if k.bit(bit) {
+                result = self.add_projective(&result, &pt);
+            }
+        }
+
+        self.to_affine(&result)
+    }
+
+    pub fn scalar_mul_base(&self, k: &Int) -> ECPoint {
+        self.scalar_mul(&self.base, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_point() {
+        let curve = &*SET8_CURVE;
+
+        assert!(curve.is_on_curve(&curve.base));
+        assert!(curve.validate_point(&curve.base));
+        assert!(!curve.validate_point(&ECPoint::new(Int::from(182), Int::from(1))));
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        let curve = &*SET8_CURVE;
+        let g = &curve.base;
+
+        let mut sum = ECPoint::Infinity;
+        for k in 0..20 {
+            assert_eq!(curve.scalar_mul(g, &Int::from(k)), sum);
+            sum = curve.add(&sum, g);
+        }
+
+        assert_eq!(curve.double(g), curve.add(g, g));
+        assert_eq!(curve.scalar_mul(g, &Int::from(-5)), curve.negate(&curve.scalar_mul(g, &Int::from(5))));
+
+        let a = Int::from_str_radix("123456789123456789123456789", 10).unwrap();
+        let b = Int::from_str_radix("987654321987654321", 10).unwrap();
+
+        assert_eq!(
+            curve.add(&curve.scalar_mul(g, &a), &curve.scalar_mul(g, &b)),
+            curve.scalar_mul(g, &(&a + &b)));
+        assert_eq!(
+            curve.scalar_mul(&curve.scalar_mul(g, &a), &b),
+            curve.scalar_mul(g, &(&a * &b)));
+    }
+
+    #[test]
+    fn test_random_point() {
+        let curve = &*SET8_CURVE;
+
+        for _ in 0..10 {
+            assert!(curve.is_on_curve(&curve.random_point()));
+        }
+
+        // The full curve has order 8 * n
+        let curve_order = &curve.order * Int::from(8);
+        let pt = curve.point_of_order(&curve_order, &Int::from(2));
+
+        assert!(curve.is_on_curve(&pt));
+        assert_eq!(curve.double(&pt), ECPoint::Infinity);
+    }
+}
@@ -0,0 +1,55 @@
+// Elliptic curve Diffie-Hellman over the curves in ec
+
+use ramp::{Int, RandomInt};
+use rand;
+
+use crate::ec::{ECCurve, ECPoint};
+use crate::ops::IntOpsExt;
+
+// (public point, private scalar) with the scalar in [1, order)
+pub fn gen_ecdh_pair(curve: &ECCurve) -> (ECPoint, Int) {
+    let priv_key = rand::thread_rng().gen_int_range(&Int::from(1), &curve.order);
+    let pub_key = curve.scalar_mul_base(&priv_key);
+
+    (pub_key, priv_key)
+}
+
+pub fn gen_shared_point(curve: &ECCurve, pub_key: &ECPoint, priv_key: &Int) -> ECPoint {
+    curve.scalar_mul(pub_key, priv_key)
+}
+
+// Key material from the shared point: x || y, empty for the point at infinity
+pub fn shared_key_bytes(curve: &ECCurve, pt: &ECPoint) -> Vec<u8> {
+    match pt {
+        ECPoint::Infinity => Vec::new(),
+        ECPoint::Point { x, y } => {
+            let len = (curve.p.bit_length() as usize + 7) / 8;
+
+            let mut bytes = x.to_bytes_len(len);
+            bytes.extend_from_slice(&y.to_bytes_len(len));
+
+            bytes
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::SET8_CURVE;
+
+    #[test]
+    fn test_ecdh() {
+        let curve = &*SET8_CURVE;
+
+        let (pub_a, priv_a) = gen_ecdh_pair(curve);
+        let (pub_b, priv_b) = gen_ecdh_pair(curve);
+
+        let s_a = gen_shared_point(curve, &pub_b, &priv_a);
+        let s_b = gen_shared_point(curve, &pub_a, &priv_b);
+
+        assert_eq!(s_a, s_b);
+        assert_eq!(shared_key_bytes(curve, &s_a).len(), 32);
+        assert!(shared_key_bytes(curve, &ECPoint::Infinity).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+// ECDSA as described in SEC 1 (http://www.secg.org/sec1-v2.pdf)
+// Deterministic nonces follow RFC 6979 (https://tools.ietf.org/html/rfc6979)
+
+use ramp::{Int, RandomInt};
+use rand;
+
+use crate::crypto_helper::{sha256, hmac_sha256};
+use crate::dsa::DSASignature;
+use crate::ec::{ECCurve, ECPoint};
+use crate::ops::IntOpsExt;
+
+#[derive(Debug, Clone)]
+pub struct ECDSAPubKey {
+    pub curve: ECCurve,
+    pub q: ECPoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct ECDSAPrivKey {
+    pub curve: ECCurve,
+    pub d: Int,
+}
+
+pub fn gen_ecdsa_pair(curve: &ECCurve) -> (ECDSAPubKey, ECDSAPrivKey) {
+    let d = rand::thread_rng().gen_int_range(&Int::from(1), &curve.order);
+
+    let q = curve.scalar_mul_base(&d);
+
+    (ECDSAPubKey { curve: curve.clone(), q }, ECDSAPrivKey { curve: curve.clone(), d })
+}
+
+// Interpret a bit string as an integer keeping only the leftmost
+// bits of it if it is longer than the group order
+fn bits_to_int(data: &[u8], n: &Int) -> Int {
+    let value = Int::from_bytes(data);
+    let data_bits = data.len() * 8;
+    let n_bits = n.bit_length() as usize;
+
+    if data_bits > n_bits {
+        value >> (data_bits - n_bits)
+    } else {
+        value
+    }
+}
+
+pub fn hash_msg_ecdsa(msg: &[u8], n: &Int) -> Int {
+    bits_to_int(&sha256(msg), n)
+}
+
+pub fn gen_deterministic_k(x: &Int, q: &Int, h1: &[u8]) -> Int {
+    let rlen = (q.bit_length() as usize + 7) / 8;
+
+    let x_octets = x.to_bytes_len(rlen);
+    let h1_octets = (bits_to_int(h1, q) % q).to_bytes_len(rlen);
+
+    let mut v = vec![0x01u8; 32];
+    let mut k = vec![0x00u8; 32];
+
+    for sep in 0..2u8 {
+        let mut input = v.clone();
+        input.push(sep);
+        input.extend_from_slice(&x_octets);
+        input.extend_from_slice(&h1_octets);
+
+        k = hmac_sha256(&k, &input);
+        v = hmac_sha256(&k, &v);
+    }
+
+    loop {
+        let mut t = Vec::new();
+
+        while t.len() < rlen {
+            v = hmac_sha256(&k, &v);
+            t.extend_from_slice(&v);
+        }
+
+        let candidate = bits_to_int(&t[..rlen], q);
+
+        if candidate >= 1 && &candidate < q {
+            return candidate;
+        }
+
+        let mut input = v.clone();
+        input.push(0x00);
+
+        k = hmac_sha256(&k, &input);
+        v = hmac_sha256(&k, &v);
+    }
+}
+
+pub fn sign_ecdsa_internal(key: &ECDSAPrivKey, msg: &[u8], k: &Int) -> DSASignature {
+    let curve = &key.curve;
+    let n = &curve.order;
+
+    let r = match curve.scalar_mul_base(k) {
+        ECPoint::Point { x, .. } => x % n,
+        ECPoint::Infinity => Int::from(0),
+    };
+
+    let e = hash_msg_ecdsa(msg, n);
+
+    let s = (k.inv_mod(n).unwrap() * (e + &key.d * &r)) % n;
+
+    DSASignature { r, s }
+}
+
+pub fn sign_ecdsa(key: &ECDSAPrivKey, msg: &[u8]) -> DSASignature {
+    loop {
+        let k = rand::thread_rng().gen_int_range(&Int::from(1), &key.curve.order);
+
+        let sig = sign_ecdsa_internal(key, msg, &k);
+
+        if sig.r != 0 && sig.s != 0 {
+            return sig;
+        }
+    }
+}
+
+pub fn sign_ecdsa_deterministic(key: &ECDSAPrivKey, msg: &[u8]) -> DSASignature {
+    let k = gen_deterministic_k(&key.d, &key.curve.order, &sha256(msg));
+
+    sign_ecdsa_internal(key, msg, &k)
+}
+
+pub fn verify_ecdsa(key: &ECDSAPubKey, msg: &[u8], sig: &DSASignature) -> bool {
+    let curve = &key.curve;
+    let n = &curve.order;
+    let (r, s) = (&sig.r, &sig.s);
+
+    if r < &1 || r >= n || s < &1 || s >= n {
+        return false;
+    }
+
+    let e = hash_msg_ecdsa(msg, n);
+    let w = s.inv_mod(n).unwrap();
+    let u1 = (e * &w) % n;
+    let u2 = (r * &w) % n;
+
+    let pt = curve.add(&curve.scalar_mul_base(&u1), &curve.scalar_mul(&key.q, &u2));
+
+    match pt {
+        ECPoint::Point { x, .. } => &(x % n) == r,
+        ECPoint::Infinity => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::SET8_CURVE;
+
+    fn p192() -> ECCurve {
+        ECCurve {
+            a: Int::from(-3),
+            b: Int::from_str_radix("64210519e59c80e70fa7e9ab72243049feb8deecc146b9b1", 16).unwrap(),
+            p: Int::from_str_radix("fffffffffffffffffffffffffffffffeffffffffffffffff", 16).unwrap(),
+            order: Int::from_str_radix("ffffffffffffffffffffffff99def836146bc9b1b4d22831", 16).unwrap(),
+            base: ECPoint::new(
+                Int::from_str_radix("188da80eb03090f67cbf20eb43a18800f4ff0afd82ff1012", 16).unwrap(),
+                Int::from_str_radix("07192b95ffc8da78631011ed6b24cdd573f977a11e794811", 16).unwrap()),
+        }
+    }
+
+    #[test]
+    fn sign_verify() {
+        let (pub_key, priv_key) = gen_ecdsa_pair(&SET8_CURVE);
+
+        let msg = b"test message";
+
+        let sig = sign_ecdsa(&priv_key, msg);
+
+        assert!(verify_ecdsa(&pub_key, msg, &sig));
+        assert!(!verify_ecdsa(&pub_key, b"other message", &sig));
+    }
+
+    #[test]
+    fn sign_rfc6979() {
+        // Test vector from RFC 6979 A.2.3 (P-192, SHA-256, "sample")
+        let curve = p192();
+        let d = Int::from_str_radix("6fab034934e4c0fc9ae67f5b5659a9d7d1fefd187ee09fd4", 16).unwrap();
+        let k = Int::from_str_radix("32b1b6d7d42a05cb449065727a84804fb1a3e34d8f261496", 16).unwrap();
+        let r = Int::from_str_radix("4b0b8ce98a92866a2820e20aa6b75b56382e0f9bfd5ecb55", 16).unwrap();
+        let s = Int::from_str_radix("ccdb006926ea9565cbadc840829d8c384e06de1f1e381b85", 16).unwrap();
+        let msg = b"sample";
+
+        assert_eq!(gen_deterministic_k(&d, &curve.order, &sha256(msg)), k);
+
+        let q = curve.scalar_mul_base(&d);
+        let priv_key = ECDSAPrivKey { curve: curve.clone(), d };
+        let pub_key = ECDSAPubKey { curve, q };
+
+        let sig = sign_ecdsa_deterministic(&priv_key, msg);
+
+        assert_eq!(sig, DSASignature { r, s });
+        assert!(verify_ecdsa(&pub_key, msg, &sig));
+    }
+}
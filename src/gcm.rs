@@ -0,0 +1,234 @@
+// AES-128 in Galois/Counter Mode
+// (https://csrc.nist.gov/publications/detail/sp/800-38d/final)
+
+use crypto::aessafe::AesSafe128Encryptor;
+use crypto::symmetriccipher::BlockEncryptor;
+
+use crate::gf128::{GF128, MulTable, BLOCK_SIZE};
+use crate::poly::{Poly, find_roots};
+
+pub const TAG_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = 12;
+
+// The blocks GHASH is computed over: the zero padded AAD, the zero padded
+// ciphertext and finally the bit lengths of both
+fn ghash_blocks(aad: &[u8], ct: &[u8]) -> Vec<GF128> {
+    let mut blocks: Vec<_> = aad.chunks(BLOCK_SIZE)
+        .chain(ct.chunks(BLOCK_SIZE))
+        .map(GF128::from_partial_block)
+        .collect();
+
+    let len_block = ((aad.len() as u128 * 8) << 64) | (ct.len() as u128 * 8);
+    blocks.push(GF128(len_block));
+
+    blocks
+}
+
+pub fn ghash(h: GF128, aad: &[u8], ct: &[u8]) -> GF128 {
+    let table = MulTable::new(h);
+
+    ghash_blocks(aad, ct).iter().fold(GF128::zero(), |y, b| table.mul(y + *b))
+}
+
+fn encrypt_block(encryptor: &AesSafe128Encryptor, block: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+    let mut output = [0u8; BLOCK_SIZE];
+
+    encryptor.encrypt_block(block, &mut output);
+
+    output
+}
+
+// Increment the rightmost 32 bits of the counter block
+fn inc32(block: &mut [u8; BLOCK_SIZE]) {
+    for b in block[12..].iter_mut().rev() {
+        *b = b.wrapping_add(1);
+
+        if *b != 0 {
+            break;
+        }
+    }
+}
+
+pub fn gcm_hash_key(key: &[u8]) -> GF128 {
+    let encryptor = AesSafe128Encryptor::new(key);
+
+    GF128::from_bytes(&encrypt_block(&encryptor, &[0u8; BLOCK_SIZE]))
+}
+
+fn initial_counter(h: GF128, nonce: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut j0 = [0u8; BLOCK_SIZE];
+
+    if nonce.len() == NONCE_SIZE {
+        j0[..NONCE_SIZE].copy_from_slice(nonce);
+        j0[BLOCK_SIZE - 1] = 1;
+    } else {
+        j0 = ghash(h, &[], nonce).to_bytes();
+    }
+
+    j0
+}
+
+fn gctr(encryptor: &AesSafe128Encryptor, j0: &[u8; BLOCK_SIZE], input: &[u8]) -> Vec<u8> {
+    let mut counter = *j0;
+    let mut output = Vec::with_capacity(input.len());
+
+    for chunk in input.chunks(BLOCK_SIZE) {
+        inc32(&mut counter);
+
+        let ks = encrypt_block(encryptor, &counter);
+
+        output.extend(chunk.iter().zip(ks.iter()).map(|(b, k)| *b ^ *k));
+    }
+
+    output
+}
+
+fn compute_tag(encryptor: &AesSafe128Encryptor, h: GF128, j0: &[u8; BLOCK_SIZE], aad: &[u8], ct: &[u8]) -> Vec<u8> {
+    let s = ghash(h, aad, ct);
+    let mask = GF128::from_bytes(&encrypt_block(encryptor, j0));
+
+    (s + mask).to_bytes().to_vec()
+}
+
+// Returns (ciphertext, tag)
+pub fn gcm_seal(key: &[u8], nonce: &[u8], aad: &[u8], pt: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let encryptor = AesSafe128Encryptor::new(key);
+    let h = GF128::from_bytes(&encrypt_block(&encryptor, &[0u8; BLOCK_SIZE]));
+    let j0 = initial_counter(h, nonce);
+
+    let ct = gctr(&encryptor, &j0, pt);
+    let tag = compute_tag(&encryptor, h, &j0, aad, &ct);
+
+    (ct, tag)
+}
+
+pub fn gcm_open(key: &[u8], nonce: &[u8], aad: &[u8], ct: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+    if tag.len() != TAG_SIZE {
+        return None;
+    }
+
+    gcm_open_truncated(key, nonce, aad, ct, tag)
+}
+
+// Accepts tags truncated to their first tag.len() bytes. An empty tag
+// would authenticate anything so it's rejected like an overlong one.
+pub fn gcm_open_truncated(key: &[u8], nonce: &[u8], aad: &[u8], ct: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+    if tag.is_empty() || tag.len() > TAG_SIZE {
+        return None;
+    }
+
+    let encryptor = AesSafe128Encryptor::new(key);
+    let h = GF128::from_bytes(&encrypt_block(&encryptor, &[0u8; BLOCK_SIZE]));
+    let j0 = initial_counter(h, nonce);
+
+    let expected_tag = compute_tag(&encryptor, h, &j0, aad, ct);
+
+    if &expected_tag[..tag.len()] != tag {
+        return None;
+    }
+
+    Some(gctr(&encryptor, &j0, ct))
+}
+
+// GHASH is the polynomial b_1 H^m + ... + b_m H so the tag is
+// t = b_1 H^m + ... + b_m H + s where s = E_K(J0). This returns that
+// polynomial in H with the tag moved to the constant term. Two messages
+// under the same nonce share s so H is a root of the sum of their polynomials.
+// Only works with full length tags, truncated ones need gcm_short_tag.
+pub fn gcm_tag_poly(aad: &[u8], ct: &[u8], tag: &[u8]) -> Poly {
+    assert_eq!(tag.len(), TAG_SIZE, "need a full length tag");
+
+    let blocks = ghash_blocks(aad, ct);
+
+    let mut coeffs = Vec::with_capacity(blocks.len() + 1);
+    coeffs.push(GF128::from_bytes(tag));
+    coeffs.extend(blocks.iter().rev());
+
+    Poly::new(coeffs)
+}
+
+// Recover the possible values of the authentication key H from two
+// messages that were encrypted with the same key and nonce
+pub fn recover_h_candidates(
+    aad1: &[u8], ct1: &[u8], tag1: &[u8],
+    aad2: &[u8], ct2: &[u8], tag2: &[u8]) -> Vec<GF128> {
+    let f = &gcm_tag_poly(aad1, ct1, tag1) + &gcm_tag_poly(aad2, ct2, tag2);
+
+    find_roots(&f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_bytes;
+
+    // Test cases from the GCM spec
+    // (https://csrc.nist.rip/groups/ST/toolkit/BCM/documents/proposedmodes/gcm/gcm-spec.pdf)
+
+    #[test]
+    fn test_gcm_zero_key() {
+        let key = [0u8; 16];
+        let nonce = [0u8; 12];
+
+        let (ct, tag) = gcm_seal(&key, &nonce, &[], &[]);
+        assert!(ct.is_empty());
+        assert_eq!(hex::encode(&tag), "58e2fccefa7e3061367f1d57a4e7455a");
+
+        let (ct, tag) = gcm_seal(&key, &nonce, &[], &[0u8; 16]);
+        assert_eq!(hex::encode(&ct), "0388dace60b6a392f328c2b971b2fe78");
+        assert_eq!(hex::encode(&tag), "ab6e47d42cec13bdf53a67b21257bddf");
+
+        let h = gcm_hash_key(&key);
+        assert_eq!(hex::encode(&h.to_bytes()), "66e94bd4ef8a2c3b884cfa59ca342b2e");
+        assert_eq!(hex::encode(&ghash(h, &[], &ct).to_bytes()), "f38cbb1ad69223dcc3457ae5b6b0f885");
+    }
+
+    #[test]
+    fn test_gcm_aad() {
+        let key = hex::decode("feffe9928665731c6d6a8f9467308308").unwrap();
+        let nonce = hex::decode("cafebabefacedbaddecaf888").unwrap();
+        let pt = hex::decode("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39").unwrap();
+        let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2").unwrap();
+
+        let (ct, tag) = gcm_seal(&key, &nonce, &aad, &pt);
+        assert_eq!(hex::encode(&ct), "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091");
+        assert_eq!(hex::encode(&tag), "5bc94fbc3221a5db94fae95ae7121a47");
+
+        assert_eq!(gcm_open(&key, &nonce, &aad, &ct, &tag), Some(pt.clone()));
+
+        let mut bad_ct = ct.clone();
+        bad_ct[0] ^= 1;
+        assert_eq!(gcm_open(&key, &nonce, &aad, &bad_ct, &tag), None);
+        assert_eq!(gcm_open(&key, &nonce, &aad[1..], &ct, &tag), None);
+
+        assert_eq!(gcm_open_truncated(&key, &nonce, &aad, &ct, &tag[..4]), Some(pt));
+        assert_eq!(gcm_open_truncated(&key, &nonce, &aad, &bad_ct, &tag[..4]), None);
+        assert_eq!(gcm_open_truncated(&key, &nonce, &aad, &ct, &[]), None);
+
+        let mut long_tag = tag.clone();
+        long_tag.push(0);
+        assert_eq!(gcm_open_truncated(&key, &nonce, &aad, &ct, &long_tag), None);
+        assert_eq!(gcm_open(&key, &nonce, &aad, &ct, &long_tag), None);
+    }
+
+    #[test]
+    fn test_nonce_reuse() {
+        let key = random_bytes(16);
+        let nonce = random_bytes(12);
+
+        let (ct1, tag1) = gcm_seal(&key, &nonce, b"header", b"attack at dawn, bring snacks");
+        let (ct2, tag2) = gcm_seal(&key, &nonce, b"other header", b"retreat at dusk");
+
+        let candidates = recover_h_candidates(b"header", &ct1, &tag1, b"other header", &ct2, &tag2);
+
+        assert!(candidates.contains(&gcm_hash_key(&key)));
+    }
+
+    #[test]
+    #[should_panic(expected = "need a full length tag")]
+    fn test_tag_poly_truncated() {
+        let (ct, tag) = gcm_seal(&random_bytes(16), &random_bytes(12), b"", b"attack at dawn");
+
+        gcm_tag_poly(b"", &ct, &tag[..8]);
+    }
+}
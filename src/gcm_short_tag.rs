@@ -0,0 +1,263 @@
+// Ferguson's attack on GCM with short tags
+// (Ferguson, "Authentication weaknesses in GCM",
+// https://csrc.nist.gov/csrc/media/projects/block-cipher-techniques/documents/bcm/comments/cwc-gcm/ferguson2.pdf)
+//
+// The tag is a polynomial in H over the ciphertext blocks. Changing only
+// the blocks whose coefficients are H^(2^i) adds sum d_i * H^(2^i) to the
+// tag, which is linear in H because squaring is: as bits the error is
+// Ad * h with Ad = sum M(d_i) * S^i. We pick the d_i so the first rows of
+// Ad are zero, leaving only a few tag bits to chance. Every forgery that
+// gets through tells us the remaining rows are orthogonal to h. The more
+// we know about h the more rows we can zero, until only h is left.
+
+use rand::{self, Rng};
+use std::cmp;
+
+use crate::gcm::{gcm_seal, gcm_open_truncated, gcm_hash_key, ghash, NONCE_SIZE, TAG_SIZE};
+use crate::gf128::{GF128, BLOCK_SIZE};
+use crate::gf2::{BitVector, BitMatrix, gf128_mul_matrix, gf128_square_matrix};
+use crate::util::random_bytes;
+
+pub trait TagOracle {
+    // Whether the ciphertext is accepted with the given truncated tag
+    fn accepts(&self, ct: &[u8], tag: &[u8]) -> bool;
+}
+
+// Encrypts and checks messages under a fixed key and nonce with tags cut
+// down to tag_len bytes
+pub struct ShortTagOracle {
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    tag_len: usize,
+}
+
+impl ShortTagOracle {
+    pub fn new(tag_len: usize) -> Self {
+        assert!(tag_len > 0 && tag_len <= TAG_SIZE, "tag has to be 1 to 16 bytes");
+
+        ShortTagOracle { key: random_bytes(16), nonce: random_bytes(NONCE_SIZE), tag_len }
+    }
+
+    pub fn seal(&self, pt: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let (ct, mut tag) = gcm_seal(&self.key, &self.nonce, &[], pt);
+        tag.truncate(self.tag_len);
+
+        (ct, tag)
+    }
+
+    pub fn check_hash_key(&self, h: GF128) -> bool {
+        gcm_hash_key(&self.key) == h
+    }
+}
+
+impl TagOracle for ShortTagOracle {
+    fn accepts(&self, ct: &[u8], tag: &[u8]) -> bool {
+        gcm_open_truncated(&self.key, &self.nonce, &[], ct, tag).is_some()
+    }
+}
+
+// Block i of the error goes where H^(2^(i+1)) is. The length block is
+// H^1 and the last ciphertext block H^2.
+fn apply_error(ct: &mut [u8], error: &[GF128]) {
+    let blocks = ct.len() / BLOCK_SIZE;
+
+    assert_eq!(ct.len() % BLOCK_SIZE, 0);
+    assert!(blocks + 1 >= 1 << error.len());
+
+    for (i, d) in error.iter().enumerate() {
+        let index = blocks + 1 - (1 << (i + 1));
+        let block = &mut ct[index * BLOCK_SIZE..(index + 1) * BLOCK_SIZE];
+
+        for (b, e) in block.iter_mut().zip(d.to_bytes().iter()) {
+            *b ^= *e;
+        }
+    }
+}
+
+fn error_blocks(d: &BitVector) -> Vec<GF128> {
+    (0..d.len() / 128).map(|i| d.slice(i * 128, (i + 1) * 128).to_gf128()).collect()
+}
+
+// Ad = sum M(d_i) * S^(i + 1)
+fn error_matrix(error: &[GF128], squarings: &[BitMatrix]) -> BitMatrix {
+    error.iter().zip(squarings.iter()).fold(BitMatrix::zero(128, 128), |acc, (d, s)| {
+        let term = gf128_mul_matrix(*d).mul(s);
+        let rows = acc.rows().iter()
+            .zip(term.rows().iter())
+            .map(|(a, b)| {
+                let mut row = a.clone();
+                row ^= b;
+                row
+            })
+            .collect();
+
+        BitMatrix::from_rows(128, rows)
+    })
+}
+
+// The entries of the first `rows` rows of Ad * X as a linear function of
+// the error bits: a row per entry, a column per bit of the error
+fn dependency_matrix(squarings: &[BitMatrix], basis_mul: &[BitMatrix], x: &BitMatrix, rows: usize) -> BitMatrix {
+    let dim = x.num_cols();
+    let mut t = BitMatrix::zero(rows * dim, squarings.len() * 128);
+
+    for (i, s) in squarings.iter().enumerate() {
+        let y = s.mul(x);
+
+        for (b, m) in basis_mul.iter().enumerate() {
+            for r in 0..rows {
+                let mut entries = BitVector::zero(dim);
+
+                for (j, y_row) in y.rows().iter().enumerate() {
+                    if m.get(r, j) {
+                        entries ^= y_row;
+                    }
+                }
+
+                for c in 0..dim {
+                    if entries.get(c) {
+                        t.set(r * dim + c, i * 128 + b, true);
+                    }
+                }
+            }
+        }
+    }
+
+    t
+}
+
+// Recover the hash key H from a ciphertext and its truncated tag, forging
+// modified copies of it. ct.len() has to be a multiple of 16 and ct at
+// least 2^n - 1 blocks long, and the tag 1 to 16 bytes. report is called
+// after every successful forgery with how many candidates for h are left
+// (as a dimension) and the number of queries so far.
+pub fn recover_hash_key_with<O, R>(oracle: &O, ct: &[u8], tag: &[u8], n: usize, mut report: R) -> GF128
+    where O: TagOracle, R: FnMut(usize, u64) {
+    assert!(!tag.is_empty() && tag.len() <= TAG_SIZE, "tag has to be 1 to 16 bytes");
+    assert!(n > 0, "need at least one block to change");
+    assert_eq!(ct.len() % BLOCK_SIZE, 0, "ciphertext has to be whole blocks");
+    assert!(ct.len() / BLOCK_SIZE + 1 >= 1 << n, "ciphertext needs at least 2^n - 1 blocks");
+
+    let tag_bits = tag.len() * 8;
+
+    let mut squarings = vec![gf128_square_matrix()];
+    for i in 1..n {
+        let next = squarings[i - 1].mul(&squarings[0]);
+        squarings.push(next);
+    }
+
+    let basis_mul: Vec<_> = (0..128).map(|b| gf128_mul_matrix(GF128::x_pow(b))).collect();
+
+    // Equations known to hold for h and a basis of their solutions
+    let mut known = BitMatrix::zero(0, 128);
+    let mut x = BitMatrix::identity(128);
+
+    let mut rng = rand::thread_rng();
+    let mut forged = ct.to_vec();
+    let mut queries = 0;
+
+    while x.num_cols() > 1 {
+        let dim = x.num_cols();
+
+        // Leave at least one free variable in the error
+        let rows = cmp::min(tag_bits - 1, (n * 128 - 1) / dim);
+        let candidates = dependency_matrix(&squarings, &basis_mul, &x, rows).kernel();
+
+        let ad = loop {
+            let mut d = BitVector::zero(n * 128);
+
+            for v in candidates.iter() {
+                if rng.gen() {
+                    d ^= v;
+                }
+            }
+
+            if d.is_zero() {
+                continue;
+            }
+
+            let error = error_blocks(&d);
+
+            apply_error(&mut forged, &error);
+            queries += 1;
+            let accepted = oracle.accepts(&forged, tag);
+            apply_error(&mut forged, &error);
+
+            if accepted {
+                break error_matrix(&error, &squarings);
+            }
+        };
+
+        for row in ad.rows().iter().take(tag_bits) {
+            known.push_row(row.clone());
+        }
+
+        x = BitMatrix::from_columns(128, &known.kernel());
+        report(x.num_cols(), queries);
+    }
+
+    assert_eq!(x.num_cols(), 1, "no hash key fits the forgeries");
+
+    x.column(0).to_gf128()
+}
+
+pub fn recover_hash_key<O: TagOracle>(oracle: &O, ct: &[u8], tag: &[u8], n: usize) -> GF128 {
+    recover_hash_key_with(oracle, ct, tag, n, |_, _| ())
+}
+
+// With H known the tag mask E_K(J0) falls out of any valid message, so a
+// tag for any other ciphertext under the same nonce follows
+pub fn forge_tag(h: GF128, ct: &[u8], tag: &[u8], new_ct: &[u8]) -> Vec<u8> {
+    let diff = ghash(h, &[], ct) + ghash(h, &[], new_ct);
+
+    tag.iter().zip(diff.to_bytes().iter()).map(|(t, d)| t ^ d).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_matrix() {
+        // Ad * h is the change in GHASH from applying the error
+        let h = GF128(rand::thread_rng().gen());
+        let ct = random_bytes(16 * BLOCK_SIZE);
+        let d = BitVector::random(4 * 128);
+
+        let mut squarings = vec![gf128_square_matrix()];
+        for i in 1..4 {
+            let next = squarings[i - 1].mul(&squarings[0]);
+            squarings.push(next);
+        }
+
+        let error = error_blocks(&d);
+        let mut modified = ct.clone();
+        apply_error(&mut modified, &error);
+
+        let ad = error_matrix(&error, &squarings);
+        let expected = ghash(h, &[], &ct) + ghash(h, &[], &modified);
+
+        assert_eq!(ad.mul_vector(&BitVector::from_gf128(h)).to_gf128(), expected);
+    }
+
+    #[test]
+    fn test_recover_hash_key() {
+        let oracle = ShortTagOracle::new(2);
+        let (ct, tag) = oracle.seal(&random_bytes(256 * BLOCK_SIZE));
+
+        let h = recover_hash_key(&oracle, &ct, &tag, 8);
+        assert!(oracle.check_hash_key(h));
+
+        let new_ct = random_bytes(100);
+        assert!(oracle.accepts(&new_ct, &forge_tag(h, &ct, &tag, &new_ct)));
+    }
+
+    #[test]
+    #[should_panic(expected = "tag has to be 1 to 16 bytes")]
+    fn test_empty_tag() {
+        let oracle = ShortTagOracle::new(2);
+        let (ct, _) = oracle.seal(&random_bytes(256 * BLOCK_SIZE));
+
+        recover_hash_key(&oracle, &ct, &[], 8);
+    }
+}
@@ -0,0 +1,243 @@
+// Arithmetic in GF(2^128) using the GCM conventions
+// (https://csrc.nist.gov/publications/detail/sp/800-38d/final)
+//
+// Elements are reduced modulo x^128 + x^7 + x^2 + x + 1. The bit order is
+// "reflected": the most significant bit of the first byte of a block is the
+// coefficient of x^0, so in the u128 (loaded big endian) bit 127 is x^0 and
+// bit 0 is x^127.
+
+use std::ops::{Add, AddAssign, Mul, MulAssign};
+
+pub const BLOCK_SIZE: usize = 16;
+
+// x^128 = x^7 + x^2 + x + 1 in the reflected bit order
+const R: u128 = 0xe1 << 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GF128(pub u128);
+
+impl GF128 {
+    pub fn zero() -> Self {
+        GF128(0)
+    }
+
+    pub fn one() -> Self {
+        GF128(1 << 127)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), BLOCK_SIZE);
+
+        GF128(bytes.iter().fold(0u128, |acc, b| (acc << 8) | u128::from(*b)))
+    }
+
+    // Blocks shorter than 16 bytes are zero padded on the right
+    pub fn from_partial_block(bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= BLOCK_SIZE);
+
+        let mut block = [0u8; BLOCK_SIZE];
+        block[..bytes.len()].copy_from_slice(bytes);
+
+        GF128::from_bytes(&block)
+    }
+
+    pub fn to_bytes(self) -> [u8; BLOCK_SIZE] {
+        let mut bytes = [0u8; BLOCK_SIZE];
+
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = (self.0 >> (8 * (BLOCK_SIZE - 1 - i))) as u8;
+        }
+
+        bytes
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    // Coefficient of x^i
+    pub fn coeff(self, i: usize) -> bool {
+        assert!(i < 128);
+
+        (self.0 >> (127 - i)) & 1 == 1
+    }
+
+    // The element x^i
+    pub fn x_pow(i: usize) -> Self {
+        assert!(i < 128);
+
+        GF128(1 << (127 - i))
+    }
+
+    pub fn square(self) -> GF128 {
+        self * self
+    }
+
+    pub fn pow(self, exp: u128) -> GF128 {
+        let mut result = GF128::one();
+
+        for i in (0..128).rev() {
+            result = result.square();
+
+            if (exp >> i) & 1 == 1 {
+                result = result * self;
+            }
+        }
+
+        result
+    }
+
+    // a^-1 = a^(2^128 - 2)
+    pub fn inverse(self) -> Option<GF128> {
+        if self.is_zero() {
+            return None;
+        }
+
+        let mut result = GF128::one();
+        let mut t = self;
+
+        for _ in 1..128 {
+            t = t.square();
+            result = result * t;
+        }
+
+        Some(result)
+    }
+
+    // Every element has a unique square root since squaring is the
+    // Frobenius automorphism: sqrt(a) = a^(2^127)
+    pub fn sqrt(self) -> GF128 {
+        let mut result = self;
+
+        for _ in 0..127 {
+            result = result.square();
+        }
+
+        result
+    }
+}
+
+// Multiples of a fixed element h for every value of every byte of the
+// other operand (Shoup's method), so a product is 16 lookups. Worth it when
+// the same h is used over and over like in GHASH.
+pub struct MulTable {
+    tables: Vec<[GF128; 256]>,
+}
+
+impl MulTable {
+    pub fn new(h: GF128) -> Self {
+        let mut tables = vec![[GF128::zero(); 256]; BLOCK_SIZE];
+
+        // h * x^i, walking down from the most significant bit
+        let mut v = h.0;
+
+        for i in 0..128 {
+            let (byte, bit) = (i / 8, 7 - i % 8);
+            tables[byte][1 << bit] = GF128(v);
+
+            if v & 1 == 1 {
+                v = (v >> 1) ^ R;
+            } else {
+                v >>= 1;
+            }
+        }
+
+        // Everything else by linearity
+        for table in tables.iter_mut() {
+            for b in 1..256 {
+                let low = b & (!b + 1);
+                table[b] = table[b ^ low] + table[low];
+            }
+        }
+
+        MulTable { tables }
+    }
+
+    pub fn mul(&self, x: GF128) -> GF128 {
+        self.tables.iter()
+            .zip(x.to_bytes().iter())
+            .fold(GF128::zero(), |acc, (table, b)| acc + table[*b as usize])
+    }
+}
+
+impl Add for GF128 {
+    type Output = GF128;
+
+    fn add(self, other: GF128) -> GF128 {
+        GF128(self.0 ^ other.0)
+    }
+}
+
+impl AddAssign for GF128 {
+    fn add_assign(&mut self, other: GF128) {
+        self.0 ^= other.0;
+    }
+}
+
+impl Mul for GF128 {
+    type Output = GF128;
+
+    // Algorithm 1 from SP 800-38D
+    fn mul(self, other: GF128) -> GF128 {
+        let mut z = 0u128;
+        let mut v = other.0;
+
+        for i in 0..128 {
+            if (self.0 >> (127 - i)) & 1 == 1 {
+                z ^= v;
+            }
+
+            if v & 1 == 1 {
+                v = (v >> 1) ^ R;
+            } else {
+                v >>= 1;
+            }
+        }
+
+        GF128(z)
+    }
+}
+
+impl MulAssign for GF128 {
+    fn mul_assign(&mut self, other: GF128) {
+        *self = *self * other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_ops() {
+        let a = GF128::from_bytes(&hex::decode("66e94bd4ef8a2c3b884cfa59ca342b2e").unwrap());
+        let b = GF128::from_bytes(&hex::decode("0388dace60b6a392f328c2b971b2fe78").unwrap());
+
+        assert_eq!(a * GF128::one(), a);
+        assert_eq!(a * b, b * a);
+        assert_eq!(a * a.inverse().unwrap(), GF128::one());
+        assert_eq!(a.sqrt().square(), a);
+        assert_eq!(a.pow(3), a * a * a);
+        assert_eq!(GF128::x_pow(1) * GF128::x_pow(2), GF128::x_pow(3));
+
+        // x^127 * x = x^128 = x^7 + x^2 + x + 1
+        let reduced = GF128::x_pow(7) + GF128::x_pow(2) + GF128::x_pow(1) + GF128::one();
+        assert_eq!(GF128::x_pow(127) * GF128::x_pow(1), reduced);
+    }
+
+    #[test]
+    fn test_mul_table() {
+        let a = GF128::from_bytes(&hex::decode("66e94bd4ef8a2c3b884cfa59ca342b2e").unwrap());
+        let table = MulTable::new(a);
+
+        let mut b = GF128::from_bytes(&hex::decode("0388dace60b6a392f328c2b971b2fe78").unwrap());
+
+        for _ in 0..100 {
+            assert_eq!(table.mul(b), a * b);
+            b = b * b + GF128::one();
+        }
+
+        assert_eq!(table.mul(GF128::one()), a);
+        assert_eq!(table.mul(GF128::zero()), GF128::zero());
+    }
+}
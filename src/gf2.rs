@@ -0,0 +1,391 @@
+// Linear algebra over GF(2)
+
+use rand::{self, Rng};
+use std::ops::BitXorAssign;
+
+use crate::gf128::GF128;
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    pub fn zero(len: usize) -> Self {
+        BitVector { len, words: vec![0; (len + WORD_BITS - 1) / WORD_BITS] }
+    }
+
+    pub fn random(len: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let mut v = BitVector::zero(len);
+
+        for word in v.words.iter_mut() {
+            *word = rng.gen();
+        }
+
+        v.clear_unused();
+
+        v
+    }
+
+    pub fn from_bits(bits: &[bool]) -> Self {
+        let mut v = BitVector::zero(bits.len());
+
+        for (i, bit) in bits.iter().enumerate() {
+            v.set(i, *bit);
+        }
+
+        v
+    }
+
+    // Bit i is the coefficient of x^i
+    pub fn from_gf128(a: GF128) -> Self {
+        let reversed = a.0.reverse_bits();
+
+        BitVector { len: 128, words: vec![reversed as u64, (reversed >> 64) as u64] }
+    }
+
+    pub fn to_gf128(&self) -> GF128 {
+        assert_eq!(self.len, 128);
+
+        let reversed = u128::from(self.words[0]) | (u128::from(self.words[1]) << 64);
+
+        GF128(reversed.reverse_bits())
+    }
+
+    // Keep the bits past len zero so comparisons work
+    fn clear_unused(&mut self) {
+        let extra = self.words.len() * WORD_BITS - self.len;
+
+        if extra > 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= !0u64 >> extra;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+
+        (self.words[i / WORD_BITS] >> (i % WORD_BITS)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < self.len);
+
+        let mask = 1 << (i % WORD_BITS);
+
+        if bit {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn dot(&self, other: &BitVector) -> bool {
+        assert_eq!(self.len, other.len);
+
+        let ones: u32 = self.words.iter().zip(other.words.iter()).map(|(a, b)| (a & b).count_ones()).sum();
+
+        ones % 2 == 1
+    }
+
+    // Bits start..end as a new vector
+    pub fn slice(&self, start: usize, end: usize) -> BitVector {
+        assert!(start <= end && end <= self.len);
+
+        let mut v = BitVector::zero(end - start);
+
+        for i in start..end {
+            v.set(i - start, self.get(i));
+        }
+
+        v
+    }
+}
+
+impl BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &BitVector) {
+        assert_eq!(self.len, other.len);
+
+        for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a ^= *b;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    cols: usize,
+    rows: Vec<BitVector>,
+}
+
+impl BitMatrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        BitMatrix { cols, rows: vec![BitVector::zero(cols); rows] }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = BitMatrix::zero(n, n);
+
+        for i in 0..n {
+            m.set(i, i, true);
+        }
+
+        m
+    }
+
+    pub fn from_rows(cols: usize, rows: Vec<BitVector>) -> Self {
+        assert!(rows.iter().all(|row| row.len() == cols));
+
+        BitMatrix { cols, rows }
+    }
+
+    pub fn from_columns(rows: usize, columns: &[BitVector]) -> Self {
+        let mut m = BitMatrix::zero(rows, columns.len());
+
+        for (j, column) in columns.iter().enumerate() {
+            assert_eq!(column.len(), rows);
+
+            for i in 0..rows {
+                m.set(i, j, column.get(i));
+            }
+        }
+
+        m
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        self.rows[i].get(j)
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, bit: bool) {
+        self.rows[i].set(j, bit);
+    }
+
+    pub fn row(&self, i: usize) -> &BitVector {
+        &self.rows[i]
+    }
+
+    pub fn rows(&self) -> &[BitVector] {
+        &self.rows
+    }
+
+    pub fn column(&self, j: usize) -> BitVector {
+        let mut v = BitVector::zero(self.rows.len());
+
+        for (i, row) in self.rows.iter().enumerate() {
+            v.set(i, row.get(j));
+        }
+
+        v
+    }
+
+    pub fn push_row(&mut self, row: BitVector) {
+        assert_eq!(row.len(), self.cols);
+
+        self.rows.push(row);
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let columns: Vec<_> = self.rows.to_vec();
+
+        BitMatrix::from_columns(self.cols, &columns)
+    }
+
+    pub fn mul(&self, other: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.cols, other.num_rows());
+
+        let rows = self.rows.iter()
+            .map(|row| {
+                let mut result = BitVector::zero(other.cols);
+
+                for (j, other_row) in other.rows.iter().enumerate() {
+                    if row.get(j) {
+                        result ^= other_row;
+                    }
+                }
+
+                result
+            })
+            .collect();
+
+        BitMatrix { cols: other.cols, rows }
+    }
+
+    pub fn mul_vector(&self, v: &BitVector) -> BitVector {
+        let bits: Vec<_> = self.rows.iter().map(|row| row.dot(v)).collect();
+
+        BitVector::from_bits(&bits)
+    }
+
+    // Reduced row echelon form by Gaussian elimination, returning the
+    // pivot columns. Row i has its leading one in column pivots[i].
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for j in 0..self.cols {
+            let r = pivots.len();
+
+            if r == self.rows.len() {
+                break;
+            }
+
+            let pivot = match (r..self.rows.len()).find(|i| self.rows[*i].get(j)) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+
+            self.rows.swap(r, pivot);
+
+            let pivot_row = self.rows[r].clone();
+
+            for (i, row) in self.rows.iter_mut().enumerate() {
+                if i != r && row.get(j) {
+                    *row ^= &pivot_row;
+                }
+            }
+
+            pivots.push(j);
+        }
+
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    // A basis of { x : self * x = 0 }
+    pub fn kernel(&self) -> Vec<BitVector> {
+        let mut m = self.clone();
+        let pivots = m.row_reduce();
+
+        let mut is_pivot = vec![false; self.cols];
+        for j in pivots.iter() {
+            is_pivot[*j] = true;
+        }
+
+        (0..self.cols)
+            .filter(|j| !is_pivot[*j])
+            .map(|free| {
+                let mut x = BitVector::zero(self.cols);
+                x.set(free, true);
+
+                for (i, j) in pivots.iter().enumerate() {
+                    x.set(*j, m.get(i, free));
+                }
+
+                x
+            })
+            .collect()
+    }
+}
+
+// The matrix M_c with M_c * a = c * a for elements as vectors of
+// coefficients. Column j is c * x^j.
+pub fn gf128_mul_matrix(c: GF128) -> BitMatrix {
+    let columns: Vec<_> = (0..128).map(|j| BitVector::from_gf128(c * GF128::x_pow(j))).collect();
+
+    BitMatrix::from_columns(128, &columns)
+}
+
+// The matrix S with S * a = a^2. Squaring is linear in characteristic 2.
+pub fn gf128_square_matrix() -> BitMatrix {
+    let columns: Vec<_> = (0..128).map(|j| BitVector::from_gf128(GF128::x_pow(j).square())).collect();
+
+    BitMatrix::from_columns(128, &columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_gf128() -> GF128 {
+        GF128(rand::thread_rng().gen())
+    }
+
+    #[test]
+    fn test_gf128_conversion() {
+        let a = random_gf128();
+        let v = BitVector::from_gf128(a);
+
+        assert_eq!(v.to_gf128(), a);
+
+        for i in 0..128 {
+            assert_eq!(v.get(i), a.coeff(i));
+        }
+    }
+
+    #[test]
+    fn test_gf128_matrices() {
+        let (a, c) = (random_gf128(), random_gf128());
+        let (m, s) = (gf128_mul_matrix(c), gf128_square_matrix());
+        let v = BitVector::from_gf128(a);
+
+        assert_eq!(m.mul_vector(&v).to_gf128(), c * a);
+        assert_eq!(s.mul_vector(&v).to_gf128(), a * a);
+        assert_eq!(m.mul(&s).mul_vector(&v).to_gf128(), c * a * a);
+    }
+
+    #[test]
+    fn test_kernel() {
+        let mut m = BitMatrix::zero(20, 50);
+
+        for i in 0..20 {
+            m.rows[i] = BitVector::random(50);
+        }
+
+        // Make some rows dependent
+        let mut sum = m.rows[0].clone();
+        sum ^= &m.rows[1];
+        m.rows[2] = sum;
+
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 50 - m.rank());
+        assert!(m.rank() < 20);
+
+        for x in kernel.iter() {
+            assert!(!x.is_zero());
+            assert!(m.mul_vector(x).is_zero());
+        }
+
+        // Independent
+        assert_eq!(BitMatrix::from_rows(50, kernel.clone()).rank(), kernel.len());
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut m = BitMatrix::zero(3, 5);
+        m.set(0, 4, true);
+        m.set(2, 1, true);
+
+        let t = m.transpose();
+        assert_eq!((t.num_rows(), t.num_cols()), (5, 3));
+        assert!(t.get(4, 0) && t.get(1, 2));
+        assert_eq!(t.transpose(), m);
+        assert_eq!(BitMatrix::identity(5).mul(&t), t);
+    }
+}
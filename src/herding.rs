@@ -0,0 +1,154 @@
+// Herding attack (Kelsey and Kohno, https://eprint.iacr.org/2005/281.pdf)
+//
+// Commit to a hash before knowing the message: build a diamond structure,
+// a binary tree of collisions that funnels 2^k starting states into a
+// single root, and publish the hash of the root with length padding.
+// Later, for whatever prefix we want to claim we predicted, we only need
+// one block that takes the prefix's state to any of the 2^k leaves.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use crate::md::{Compression, CountingCompression, MerkleDamgard, md_iterate};
+use crate::multicollision::find_state_collision;
+use crate::util::random_bytes;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AttackStats {
+    pub compressions: u64,
+    pub time: Duration,
+}
+
+pub struct DiamondStructure<C: Compression> {
+    // leaf state -> leaf index
+    leaves: HashMap<C::State, usize>,
+    // blocks[l][i] takes state i on level l to state i / 2 on level l + 1
+    blocks: Vec<Vec<Vec<u8>>>,
+    root: C::State,
+    prefix_len: usize,
+    pub build_stats: AttackStats,
+}
+
+impl<C> DiamondStructure<C> where C: Compression + Clone, C::State: Eq + Hash {
+    // 2^k leaves for messages whose prefix is prefix_blocks blocks long
+    pub fn new(compression: &C, k: usize, prefix_blocks: usize) -> Self {
+        let start = Instant::now();
+        let counting = CountingCompression::new(compression);
+        let block_size = compression.block_size();
+
+        // Any states will do for the leaves as long as they're distinct
+        let mut leaves = HashMap::new();
+        let mut level = Vec::with_capacity(1 << k);
+
+        while level.len() < 1 << k {
+            let mut state = counting.initial_state();
+            counting.compress(&mut state, &random_bytes(block_size));
+
+            if !leaves.contains_key(&state) {
+                leaves.insert(state.clone(), level.len());
+                level.push(state);
+            }
+        }
+
+        let mut blocks = Vec::with_capacity(k);
+
+        for _ in 0..k {
+            let mut level_blocks = Vec::with_capacity(level.len());
+            let mut next_level = Vec::with_capacity(level.len() / 2);
+
+            for pair in level.chunks(2) {
+                let (b1, b2, next) = find_state_collision(&counting, &pair[0], &pair[1]);
+
+                level_blocks.push(b1);
+                level_blocks.push(b2);
+                next_level.push(next);
+            }
+
+            blocks.push(level_blocks);
+            level = next_level;
+        }
+
+        let build_stats = AttackStats { compressions: counting.calls(), time: start.elapsed() };
+
+        DiamondStructure {
+            leaves,
+            blocks,
+            root: level.remove(0),
+            prefix_len: prefix_blocks * block_size,
+            build_stats,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.blocks.len()
+    }
+
+    // prefix + link block + one block per level
+    pub fn message_len(&self, block_size: usize) -> usize {
+        self.prefix_len + (1 + self.k()) * block_size
+    }
+
+    // The hash to publish. It covers the padding for the final message
+    // length so that has to be fixed up front.
+    pub fn commitment(&self, compression: &C) -> Vec<u8> {
+        let processed_len = self.message_len(compression.block_size()) as u64;
+
+        MerkleDamgard::with_state(compression.clone(), self.root.clone(), processed_len).finalize()
+    }
+
+    // Find a block linking the prefix into the diamond and return the full
+    // message that hashes to the commitment
+    pub fn herd(&self, compression: &C, prefix: &[u8]) -> (Vec<u8>, AttackStats) {
+        assert_eq!(prefix.len(), self.prefix_len);
+
+        let start = Instant::now();
+        let counting = CountingCompression::new(compression);
+        let prefix_state = md_iterate(&counting, &counting.initial_state(), prefix);
+
+        let (link, mut index) = loop {
+            let block = random_bytes(compression.block_size());
+
+            let mut state = prefix_state.clone();
+            counting.compress(&mut state, &block);
+
+            if let Some(index) = self.leaves.get(&state) {
+                break (block, *index);
+            }
+        };
+
+        let mut msg = prefix.to_vec();
+        msg.extend_from_slice(&link);
+
+        for level_blocks in self.blocks.iter() {
+            msg.extend_from_slice(&level_blocks[index]);
+            index /= 2;
+        }
+
+        (msg, AttackStats { compressions: counting.calls(), time: start.elapsed() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::md_digest;
+    use crate::toy_hash::ToyCompression;
+
+    #[test]
+    fn test_herding() {
+        let compression = ToyCompression::new(16);
+        let diamond = DiamondStructure::new(&compression, 4, 2);
+
+        let commitment = diamond.commitment(&compression);
+
+        for prefix in [b"Red Sox 4 Yankees 2 Mets 1 Cubs 0", b"Yankees 7 Red Sox 1 Mets 5 Cubs 3"].iter() {
+            let (msg, stats) = diamond.herd(&compression, &prefix[..32]);
+
+            assert!(msg.starts_with(&prefix[..32]));
+            assert_eq!(msg.len(), diamond.message_len(16));
+            assert_eq!(md_digest(compression, &msg), commitment);
+            assert!(stats.compressions > 0);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// HMAC (https://tools.ietf.org/html/rfc2104) over any of the crate's hashes
+
+use crate::digest::Digest;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub fn hmac<D: Digest>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // if key is too long, shorten it by hashing
+    let mut key_pad =
+        if key.len() > D::BLOCK_SIZE {
+            D::digest(key)
+        } else {
+            key.to_vec()
+        };
+
+    // pad key to block size
+    key_pad.resize(D::BLOCK_SIZE, 0);
+
+    let i_key_pad: Vec<u8> = key_pad.iter().map(|b| b ^ IPAD).collect();
+    let o_key_pad: Vec<u8> = key_pad.iter().map(|b| b ^ OPAD).collect();
+
+    // hash inner
+    let mut inner = D::new();
+    inner.update(&i_key_pad);
+    inner.update(data);
+    let i_hash = inner.finalize();
+
+    // hash outer
+    let mut outer = D::new();
+    outer.update(&o_key_pad);
+    outer.update(&i_hash);
+
+    outer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hmac;
+    use crate::sha1::Sha1;
+    use crate::sha256::Sha256;
+
+    // Test cases from RFC 2202 (HMAC-SHA-1) and RFC 4231 (HMAC-SHA-256)
+
+    #[test]
+    fn test_hmac_sha1() {
+        assert_eq!(
+            hex::encode(&hmac::<Sha1>(&[0x0b; 20], b"Hi There")),
+            "b617318655057264e28bc0b6fb378c8ef146be00");
+        assert_eq!(
+            hex::encode(&hmac::<Sha1>(b"Jefe", b"what do ya want for nothing?")),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+        assert_eq!(
+            hex::encode(&hmac::<Sha1>(&[0xaa; 20], &[0xdd; 50])),
+            "125d7342b9ac11cd91a39af48aa17b4f63f175d3");
+        assert_eq!(
+            hex::encode(&hmac::<Sha1>(&[0xaa; 80], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        assert_eq!(
+            hex::encode(&hmac::<Sha256>(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(
+            hex::encode(&hmac::<Sha256>(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(
+            hex::encode(&hmac::<Sha256>(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+    }
+}
@@ -0,0 +1,202 @@
+// Biased (EC)DSA nonces and the hidden number problem
+// (Boneh and Venkatesan, "Hardness of Computing the Most Significant Bits
+// of Secret Keys in Diffie-Hellman and Related Schemes", and Nguyen and
+// Shparlinski, "The Insecurity of the Digital Signature Algorithm with
+// Partially Known Nonces")
+//
+// With s = k^-1 * (h + d * r) and the low l bits of k known to be zero,
+// every signature gives d * t - u = k / 2^l (mod q) for known t and u where
+// k / 2^l is small. Enough of these make the vector built from d much
+// shorter than anything else in a lattice and LLL will find it.
+
+use ramp::{Int, RandomInt};
+use rand;
+
+use crate::dsa::{DSAPubKey, DSAPrivKey, DSASignature, sign_dsa_internal};
+use crate::ecdsa::{ECDSAPubKey, ECDSAPrivKey, hash_msg_ecdsa, sign_ecdsa_internal};
+use crate::lattice::{Rational, Vector, lll};
+use crate::ops::IntOpsExt;
+use crate::sha1::sha1_digest;
+
+// Nonce in [1, q) with the low `bits` bits cleared
+fn gen_biased_nonce(q: &Int, bits: u32) -> Int {
+    let mask = (Int::from(1) << bits as usize) - 1;
+
+    loop {
+        let k = rand::thread_rng().gen_uint_below(q);
+        let k = &k - (&k & &mask);
+
+        if k != 0 {
+            return k;
+        }
+    }
+}
+
+pub fn sign_ecdsa_biased(key: &ECDSAPrivKey, msg: &[u8], bits: u32) -> DSASignature {
+    loop {
+        let k = gen_biased_nonce(&key.curve.order, bits);
+        let sig = sign_ecdsa_internal(key, msg, &k);
+
+        if sig.r != 0 && sig.s != 0 {
+            return sig;
+        }
+    }
+}
+
+pub fn sign_dsa_biased(key: &DSAPrivKey, msg: &[u8], bits: u32) -> DSASignature {
+    loop {
+        let k = gen_biased_nonce(&key.common.q, bits);
+        let sig = sign_dsa_internal(key, msg, &k);
+
+        if sig.r != 0 && sig.s != 0 {
+            return sig;
+        }
+    }
+}
+
+// Rows q * e_i for each sample, then (t_1 .. t_n, 1 / 2^l, 0) and
+// (u_1 .. u_n, 0, q / 2^l). The combination d * bt - bu + sum m_i * q * e_i
+// is (k_1 / 2^l .. k_n / 2^l, d / 2^l, -q / 2^l) which is short.
+pub fn hnp_basis(q: &Int, samples: &[(Int, DSASignature)], bits: u32) -> Vec<Vector> {
+    let n = samples.len();
+    let scale = Int::from(1) << bits as usize;
+    let zero = Rational::zero();
+
+    let mut basis = Vec::with_capacity(n + 2);
+
+    for i in 0..n {
+        let mut row = vec![zero.clone(); n + 2];
+        row[i] = Rational::from_int(q.clone());
+        basis.push(row);
+    }
+
+    let mut bt = Vec::with_capacity(n + 2);
+    let mut bu = Vec::with_capacity(n + 2);
+
+    for (h, sig) in samples.iter() {
+        let w = (&sig.s * &scale).inv_mod(q).unwrap();
+
+        bt.push(Rational::from_int((&sig.r * &w) % q));
+        bu.push(Rational::from_int(q - (h * &w) % q));
+    }
+
+    bt.push(Rational::new(Int::from(1), scale.clone()));
+    bt.push(zero.clone());
+    bu.push(zero);
+    bu.push(Rational::new(q.clone(), scale));
+
+    basis.push(bt);
+    basis.push(bu);
+
+    basis
+}
+
+// samples are (message hash, signature) pairs for nonces whose low `bits`
+// bits are zero. is_key checks a candidate, usually against the public key.
+pub fn solve_hnp<F: Fn(&Int) -> bool>(q: &Int, samples: &[(Int, DSASignature)], bits: u32, is_key: F) -> Option<Int> {
+    let n = samples.len();
+    let scale = Int::from(1) << bits as usize;
+    let cu = Rational::new(q.clone(), scale.clone());
+    let neg_cu = -&cu;
+
+    let delta = Rational::new(Int::from(99), Int::from(100));
+    let reduced = lll(&hnp_basis(q, samples, bits), &delta);
+
+    for row in reduced.iter() {
+        // row[n] is +-d / 2^l
+        let d = &row[n] * &Rational::from_int(scale.clone());
+
+        if d.den() != &1 {
+            continue;
+        }
+
+        let d = if row[n + 1] == neg_cu {
+            d.num().clone()
+        } else if row[n + 1] == cu {
+            -d.num().clone()
+        } else {
+            continue;
+        };
+
+        let d = ((d % q) + q) % q;
+
+        if is_key(&d) {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+pub fn recover_ecdsa_key(key: &ECDSAPubKey, sigs: &[(Vec<u8>, DSASignature)], bits: u32) -> Option<Int> {
+    let curve = &key.curve;
+    let n = &curve.order;
+
+    let samples: Vec<_> = sigs.iter()
+        .map(|(msg, sig)| (hash_msg_ecdsa(msg, n), sig.clone()))
+        .collect();
+
+    solve_hnp(n, &samples, bits, |d| curve.scalar_mul_base(d) == key.q)
+}
+
+pub fn recover_dsa_key(key: &DSAPubKey, sigs: &[(Vec<u8>, DSASignature)], bits: u32) -> Option<Int> {
+    let (p, q, g) = (&key.common.p, &key.common.q, &key.common.g);
+
+    let samples: Vec<_> = sigs.iter()
+        .map(|(msg, sig)| (Int::from_bytes(&sha1_digest(msg)), sig.clone()))
+        .collect();
+
+    solve_hnp(q, &samples, bits, |x| g.pow_mod(x, p) == key.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::{gen_dsa_pair, verify_dsa};
+    use crate::ec::SET8_CURVE;
+    use crate::ecdsa::{gen_ecdsa_pair, verify_ecdsa};
+
+    fn sign_many<S: Fn(&[u8]) -> DSASignature>(count: usize, sign: S) -> Vec<(Vec<u8>, DSASignature)> {
+        (0..count)
+            .map(|i| {
+                let msg = format!("message {}", i).into_bytes();
+                let sig = sign(&msg);
+
+                (msg, sig)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_biased_nonce() {
+        let q = Int::from(1_000_003);
+
+        for _ in 0..100 {
+            let k = gen_biased_nonce(&q, 8);
+
+            assert!(k > 0 && k < q);
+            assert!(k.trailing_zeros() >= 8);
+        }
+    }
+
+    #[test]
+    fn test_recover_ecdsa_key() {
+        let (pub_key, priv_key) = gen_ecdsa_pair(&SET8_CURVE);
+        let sigs = sign_many(9, |msg| sign_ecdsa_biased(&priv_key, msg, 20));
+
+        for (msg, sig) in sigs.iter() {
+            assert!(verify_ecdsa(&pub_key, msg, sig));
+        }
+
+        assert_eq!(recover_ecdsa_key(&pub_key, &sigs, 20), Some(priv_key.d));
+    }
+
+    #[test]
+    fn test_recover_dsa_key() {
+        let (pub_key, priv_key) = gen_dsa_pair();
+        let sigs = sign_many(11, |msg| sign_dsa_biased(&priv_key, msg, 20));
+
+        assert!(verify_dsa(&pub_key, &sigs[0].0, &sigs[0].1));
+        assert_eq!(recover_dsa_key(&pub_key, &sigs, 20), Some(priv_key.x));
+    }
+}
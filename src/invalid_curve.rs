@@ -0,0 +1,185 @@
+// Invalid curve attack on ECDH
+// (Biehl, Meyer and Muller, "Differential Fault Attacks on Elliptic Curve
+// Cryptosystems")
+//
+// The addition formulas never use b, so if Bob doesn't check that our
+// point is on his curve he'll happily multiply a point from another curve
+// y^2 = x^3 + ax + b'. Pick curves whose order has small factors r, send a
+// point of order r and brute force Bob's key mod r from his MAC, as with
+// the DH small subgroup attack.
+//
+// Counting the points on a curve this size needs Schoof's algorithm, which
+// we don't have, so the bogus curves and their orders are the ones given in
+// challenge 59. They only work against the set 8 curve, so that's the only
+// curve collect_residues and recover_key support.
+
+use ramp::int::Int;
+
+use crate::crypto_helper::hmac_sha256;
+use crate::ec::{ECCurve, ECPoint, SET8_CURVE};
+use crate::ecdh::{gen_ecdh_pair, gen_shared_point, shared_key_bytes};
+use crate::ops::crt;
+use crate::prime::factor_small;
+
+pub const MESSAGE: &[u8] = b"crazy flamboyant for the rap enjoyment";
+
+// Curves sharing a and p with the set 8 curve: (b', order of the curve)
+pub const CHALLENGE59_BOGUS_CURVES: [(i64, &str); 3] = [
+    (210, "233970423115425145550826547352470124412"),
+    (504, "233970423115425145544350131142039591210"),
+    (727, "233970423115425145545378039958152057148"),
+];
+
+fn mac(curve: &ECCurve, shared: &ECPoint, msg: &[u8]) -> Vec<u8> {
+    hmac_sha256(&shared_key_bytes(curve, shared), msg)
+}
+
+pub struct EcdhBob {
+    curve: ECCurve,
+    priv_key: Int,
+    pub pub_key: ECPoint,
+}
+
+impl EcdhBob {
+    pub fn new(curve: &ECCurve) -> Self {
+        let (pub_key, priv_key) = gen_ecdh_pair(curve);
+
+        EcdhBob { curve: curve.clone(), priv_key, pub_key }
+    }
+
+    // Bob doesn't check that the point is on his curve
+    pub fn respond(&self, pub_key: &ECPoint) -> (Vec<u8>, Vec<u8>) {
+        let shared = gen_shared_point(&self.curve, pub_key, &self.priv_key);
+
+        (MESSAGE.to_vec(), mac(&self.curve, &shared, MESSAGE))
+    }
+
+    pub fn check_key(&self, priv_key: &Int) -> bool {
+        *priv_key == self.priv_key
+    }
+}
+
+pub fn challenge59_bogus_curves() -> Vec<(ECCurve, Int)> {
+    CHALLENGE59_BOGUS_CURVES.iter()
+        .map(|(b, order)| {
+            (ECCurve { b: Int::from(*b), ..SET8_CURVE.clone() }, Int::from_str_radix(order, 10).unwrap())
+        })
+        .collect()
+}
+
+// Find k mod r given a MAC keyed with k * pt, pt being of order r
+pub fn brute_force_residue(curve: &ECCurve, pt: &ECPoint, r: u64, msg: &[u8], tag: &[u8]) -> Option<u64> {
+    let mut shared = ECPoint::Infinity;
+
+    for k in 0..r {
+        if mac(curve, &shared, msg) == tag {
+            return Some(k);
+        }
+
+        shared = curve.add(&shared, pt);
+    }
+
+    None
+}
+
+// Whether the challenge 59 curves share a and p with Bob's curve
+fn supported(bob: &EcdhBob) -> bool {
+    bob.curve.a == SET8_CURVE.a && bob.curve.p == SET8_CURVE.p
+}
+
+// Residues of Bob's key modulo small factors of the bogus curves' orders,
+// stopping once the product of the moduli passes the order of Bob's base
+// point. Only Bobs on the set 8 curve are supported, for anyone else there
+// are no residues.
+pub fn collect_residues(bob: &EcdhBob, bound: u64) -> Vec<(Int, Int)> {
+    if !supported(bob) {
+        return Vec::new();
+    }
+
+    let mut residues = Vec::new();
+    let mut used = Vec::new();
+    let mut modulus = Int::from(1);
+
+    for (bogus, curve_order) in challenge59_bogus_curves() {
+        for r in factor_small(&curve_order, bound) {
+            // the moduli have to be coprime for the CRT
+            if used.contains(&r) {
+                continue;
+            }
+
+            let pt = bogus.point_of_order(&curve_order, &Int::from(r));
+            let (msg, tag) = bob.respond(&pt);
+            let k = brute_force_residue(&bogus, &pt, r, &msg, &tag).expect("no residue matches the MAC");
+
+            used.push(r);
+            modulus *= Int::from(r);
+            residues.push((Int::from(k), Int::from(r)));
+
+            if modulus > bob.curve.order {
+                return residues;
+            }
+        }
+    }
+
+    residues
+}
+
+// None if Bob isn't on the set 8 curve or the factors below bound don't
+// cover his key
+pub fn recover_key(bob: &EcdhBob, bound: u64) -> Option<Int> {
+    if !supported(bob) {
+        return None;
+    }
+
+    let (k, modulus) = crt(&collect_residues(bob, bound));
+
+    if modulus > bob.curve.order {
+        Some(k)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bogus_curves() {
+        let curve = &*SET8_CURVE;
+
+        for (bogus, order) in challenge59_bogus_curves() {
+            let pt = bogus.random_point();
+
+            assert!(bogus.is_on_curve(&pt));
+            assert!(!curve.is_on_curve(&pt));
+            assert!(!curve.validate_point(&pt));
+            assert!(bogus.scalar_mul(&pt, &order).is_infinity());
+        }
+    }
+
+    #[test]
+    fn test_collect_residues() {
+        let curve = &*SET8_CURVE;
+        let bob = EcdhBob::new(curve);
+
+        // Only the small factors to keep this quick, c59 does the rest
+        let residues = collect_residues(&bob, 1000);
+        assert_eq!(residues.len(), 12);
+
+        for (k, r) in residues.iter() {
+            assert_eq!(*k, &bob.priv_key % r);
+        }
+
+        assert_eq!(recover_key(&bob, 1000), None);
+    }
+
+    #[test]
+    fn test_unsupported_curve() {
+        let curve = ECCurve { a: Int::from(3), ..SET8_CURVE.clone() };
+        let bob = EcdhBob { curve, priv_key: Int::from(12345), pub_key: ECPoint::Infinity };
+
+        assert!(collect_residues(&bob, 1000).is_empty());
+        assert_eq!(recover_key(&bob, 1000), None);
+    }
+}
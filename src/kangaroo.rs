@@ -0,0 +1,295 @@
+// Pollard's kangaroo (lambda) algorithm for discrete logs in an interval
+// (https://arxiv.org/pdf/0812.0789.pdf)
+//
+// A tame kangaroo starts at g^b and makes N pseudorandom jumps, the length
+// of each depending only on where it lands, and leaves a trap where it
+// stops. A wild kangaroo starting at y follows the same rule, so once it
+// lands anywhere on the tame kangaroo's path it follows it into the trap.
+// Takes about sqrt(b - a) steps.
+
+use ramp::int::Int;
+use std::slice;
+
+use crate::dh::DhGroup;
+use crate::ec::{ECCurve, ECPoint};
+use crate::ops::{IntOpsExt, crt};
+use crate::subgroup_confinement::{Bob, collect_residues};
+
+// What the kangaroos need from a group, written multiplicatively
+pub trait KangarooGroup {
+    type Element: Clone + PartialEq;
+
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn pow(&self, g: &Self::Element, exp: &Int) -> Self::Element;
+
+    // Any deterministic function of the element, used to pick jumps
+    fn hash(&self, e: &Self::Element) -> usize;
+}
+
+// the low bits of an Int are as good a hash as any
+fn low_bits(n: &Int) -> usize {
+    (0..16).fold(0, |acc, i| acc | (n.bit(i) as usize) << i)
+}
+
+// Z_p*
+pub struct MultiplicativeGroup {
+    pub p: Int,
+}
+
+impl KangarooGroup for MultiplicativeGroup {
+    type Element = Int;
+
+    fn mul(&self, a: &Int, b: &Int) -> Int {
+        (a * b) % &self.p
+    }
+
+    fn pow(&self, g: &Int, exp: &Int) -> Int {
+        g.pow_mod(exp, &self.p)
+    }
+
+    fn hash(&self, e: &Int) -> usize {
+        low_bits(e)
+    }
+}
+
+impl KangarooGroup for ECCurve {
+    type Element = ECPoint;
+
+    fn mul(&self, a: &ECPoint, b: &ECPoint) -> ECPoint {
+        self.add(a, b)
+    }
+
+    fn pow(&self, g: &ECPoint, exp: &Int) -> ECPoint {
+        self.scalar_mul(g, exp)
+    }
+
+    fn hash(&self, e: &ECPoint) -> usize {
+        match e {
+            ECPoint::Infinity => 0,
+            ECPoint::Point { x, .. } => low_bits(x),
+        }
+    }
+}
+
+// Jump table: f(y) = 2^(y mod k), and g^f(y) precomputed
+struct Jumps<G: KangarooGroup> {
+    distances: Vec<Int>,
+    factors: Vec<G::Element>,
+}
+
+impl<G: KangarooGroup> Jumps<G> {
+    fn new(group: &G, g: &G::Element, k: u32) -> Self {
+        let distances = jump_distances(k);
+        let factors = distances.iter().map(|d| group.pow(g, d)).collect();
+
+        Jumps { distances, factors }
+    }
+
+    fn index(&self, group: &G, y: &G::Element) -> usize {
+        group.hash(y) % self.distances.len()
+    }
+}
+
+fn jump_distances(k: u32) -> Vec<Int> {
+    (0..k).map(|i| Int::from(1) << i as usize).collect()
+}
+
+// Where a tame kangaroo starting at g^b stopped after n jumps. Any number
+// of wild kangaroos can be run against the same trap.
+struct Trap<G: KangarooGroup> {
+    jumps: Jumps<G>,
+    x_tame: Int,
+    y_tame: G::Element,
+}
+
+impl<G: KangarooGroup> Trap<G> {
+    fn new(group: &G, g: &G::Element, b: &Int, k: u32, n: &Int) -> Self {
+        let jumps = Jumps::new(group, g, k);
+
+        let mut x_tame = Int::from(0);
+        let mut y_tame = group.pow(g, b);
+        let mut i = Int::from(0);
+
+        while &i < n {
+            let j = jumps.index(group, &y_tame);
+
+            x_tame += &jumps.distances[j];
+            y_tame = group.mul(&y_tame, &jumps.factors[j]);
+            i += 1;
+        }
+
+        Trap { jumps, x_tame, y_tame }
+    }
+
+    // Run a wild kangaroo from y, giving x if it lands in the trap
+    fn catch(&self, group: &G, y: &G::Element, a: &Int, b: &Int) -> Option<Int> {
+        // The wild kangaroo has passed the trap once it's gone further than
+        // the tame one could have from any x in the interval
+        let limit = b - a + &self.x_tame;
+        let mut x_wild = Int::from(0);
+        let mut y_wild = y.clone();
+
+        while x_wild <= limit {
+            if y_wild == self.y_tame {
+                return Some(b + &self.x_tame - &x_wild);
+            }
+
+            let j = self.jumps.index(group, &y_wild);
+
+            x_wild += &self.jumps.distances[j];
+            y_wild = group.mul(&y_wild, &self.jumps.factors[j]);
+        }
+
+        None
+    }
+}
+
+// Find x in [a, b] with g^x = y in any group, using jumps of up to
+// 2^(k-1) and a tame kangaroo making n jumps
+pub fn kangaroo_in<G>(group: &G, g: &G::Element, y: &G::Element, a: &Int, b: &Int, k: u32, n: &Int) -> Option<Int>
+    where G: KangarooGroup {
+    Trap::new(group, g, b, k, n).catch(group, y, a, b)
+}
+
+// A single run with the given parameters, None if the wild kangaroo
+// misses the trap even though x is in [a, b]
+pub fn kangaroo_with(g: &Int, y: &Int, p: &Int, a: &Int, b: &Int, k: u32, n: &Int) -> Option<Int> {
+    kangaroo_in(&MultiplicativeGroup { p: p.clone() }, g, y, a, b, k, n)
+}
+
+// Jump sizes averaging about sqrt(b - a) / 2 and a tame kangaroo running
+// four times the mean jump
+pub fn kangaroo_params(a: &Int, b: &Int) -> (u32, Int) {
+    let target = (b - a).nth_root(2) / Int::from(2);
+    let mut k = 1;
+
+    while (Int::from(1) << k as usize) / Int::from(k) < target {
+        k += 1;
+    }
+
+    let distances = jump_distances(k);
+    let mean = distances.iter().fold(Int::from(0), |acc, d| acc + d) / Int::from(k);
+
+    (k, mean * Int::from(4))
+}
+
+// Runs that miss are retried with twice the jump sizes and twice as many
+// tame jumps, which gives a different path, before giving up
+const ATTEMPTS: u32 = 3;
+
+// The tame kangaroo only depends on g and the interval, so each attempt
+// lays one trap for all of ys. Gives the index of the first y caught and
+// its log.
+fn kangaroo_retrying<G: KangarooGroup>(group: &G, g: &G::Element, ys: &[G::Element], a: &Int, b: &Int) -> Option<(usize, Int)> {
+    let (k, n) = kangaroo_params(a, b);
+
+    (0..ATTEMPTS).find_map(|i| {
+        let trap = Trap::new(group, g, b, k + i, &(&n << i as usize));
+
+        ys.iter().enumerate().find_map(|(j, y)| trap.catch(group, y, a, b).map(|x| (j, x)))
+    })
+}
+
+// None means y is almost certainly not g^x for any x in [a, b]
+pub fn kangaroo(g: &Int, y: &Int, p: &Int, a: &Int, b: &Int) -> Option<Int> {
+    kangaroo_retrying(&MultiplicativeGroup { p: p.clone() }, g, slice::from_ref(y), a, b).map(|(_, x)| x)
+}
+
+// kangaroo over an elliptic curve: k in [a, b] with k * g = y
+pub fn ec_kangaroo(curve: &ECCurve, g: &ECPoint, y: &ECPoint, a: &Int, b: &Int) -> Option<Int> {
+    ec_kangaroo_any(curve, g, slice::from_ref(y), a, b).map(|(_, k)| k)
+}
+
+// ec_kangaroo for whichever of ys has its log in [a, b], without walking
+// the tame kangaroo again for each of them
+pub fn ec_kangaroo_any(curve: &ECCurve, g: &ECPoint, ys: &[ECPoint], a: &Int, b: &Int) -> Option<(usize, Int)> {
+    kangaroo_retrying(curve, g, ys, a, b)
+}
+
+// When the small factors of (p - 1) / q don't cover q, the subgroup attack
+// only gives x mod r. With x = n + m * r, y * g^-n = (g^r)^m and m is in
+// [0, (q - 1) / r], small enough for the kangaroo.
+pub fn recover_key(group: &DhGroup, bob: &Bob, bound: u64) -> Option<Int> {
+    let residues = collect_residues(group, bob, bound);
+    let (n, r) = crt(&residues);
+
+    let g_inv_n = group.g.pow_mod(&n, &group.p).inv_mod(&group.p).unwrap();
+    let y = (&bob.pub_key * g_inv_n) % &group.p;
+    let g = group.g.pow_mod(&r, &group.p);
+
+    let m = kangaroo(&g, &y, &group.p, &Int::from(0), &((&group.q - 1) / &r))?;
+
+    Some(n + m * r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ramp::RandomInt;
+    use crate::dh::CH58_GROUP;
+    use crate::ec::SET8_CURVE;
+
+    #[test]
+    fn test_kangaroo() {
+        let group = &*CH58_GROUP;
+        let y = Int::from_str_radix("7760073848032689505395005705677365876654629189298052775754597607446617558600394076764814236081991643094239886772481052254010323780165093955236429914607119", 10).unwrap();
+
+        let x = kangaroo(&group.g, &y, &group.p, &Int::from(0), &Int::from(1 << 20)).unwrap();
+
+        assert_eq!(group.g.pow_mod(&x, &group.p), y);
+    }
+
+    #[test]
+    fn test_kangaroo_offset() {
+        let group = &*CH58_GROUP;
+        let (a, b) = (Int::from(1_000_000), Int::from(1_100_000));
+
+        for x in [1_000_000, 1_012_345, 1_100_000].iter() {
+            let y = group.g.pow_mod(&Int::from(*x), &group.p);
+
+            assert_eq!(kangaroo(&group.g, &y, &group.p, &a, &b), Some(Int::from(*x)));
+        }
+    }
+
+    #[test]
+    fn test_kangaroo_random() {
+        // Small intervals make single runs miss now and then
+        let group = &*CH58_GROUP;
+        let b = Int::from(4095);
+
+        for _ in 0..50 {
+            let x = rand::thread_rng().gen_int_range(&Int::from(0), &(&b + 1));
+            let y = group.g.pow_mod(&x, &group.p);
+
+            assert_eq!(kangaroo(&group.g, &y, &group.p, &Int::from(0), &b), Some(x));
+        }
+    }
+
+    #[test]
+    fn test_ec_kangaroo() {
+        let curve = &*SET8_CURVE;
+        let (a, b) = (Int::from(1 << 20), Int::from(1 << 21));
+        let k = Int::from(1_234_567);
+
+        let y = curve.scalar_mul_base(&k);
+
+        assert_eq!(ec_kangaroo(curve, &curve.base, &y, &a, &b), Some(k));
+    }
+
+    #[test]
+    fn test_ec_kangaroo_any() {
+        let curve = &*SET8_CURVE;
+        let (a, b) = (Int::from(0), Int::from(1 << 20));
+        let k = Int::from(654_321);
+
+        // only the last point has its log in the interval
+        let ys = [
+            curve.scalar_mul_base(&(&curve.order - &k)),
+            curve.scalar_mul_base(&(&k + (1 << 21))),
+            curve.scalar_mul_base(&k),
+        ];
+
+        assert_eq!(ec_kangaroo_any(curve, &curve.base, &ys, &a, &b), Some((2, k)));
+    }
+}
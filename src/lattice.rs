@@ -0,0 +1,285 @@
+// Lattice basis reduction with exact rational arithmetic
+// LLL as in Cohen, "A Course in Computational Algebraic Number Theory",
+// algorithm 2.6.3, keeping the Gram-Schmidt coefficients up to date
+// instead of recomputing them after every change.
+
+use ramp::int::Int;
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+// num / den in lowest terms with den > 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rational {
+    num: Int,
+    den: Int,
+}
+
+impl Rational {
+    pub fn new(num: Int, den: Int) -> Self {
+        assert!(den != 0);
+
+        let gcd = num.gcd(&den);
+        let (num, den) = (num / &gcd, den / &gcd);
+
+        if den < 0 {
+            Rational { num: -num, den: -den }
+        } else {
+            Rational { num, den }
+        }
+    }
+
+    pub fn from_int(n: Int) -> Self {
+        Rational { num: n, den: Int::from(1) }
+    }
+
+    pub fn zero() -> Self {
+        Rational::from_int(Int::from(0))
+    }
+
+    pub fn num(&self) -> &Int {
+        &self.num
+    }
+
+    pub fn den(&self) -> &Int {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational { num: self.num.abs(), den: self.den.clone() }
+    }
+
+    // Nearest integer, halves rounding up
+    pub fn round(&self) -> Int {
+        let twice = &self.num * Int::from(2) + &self.den;
+        let den = &self.den * Int::from(2);
+
+        // floor division
+        let (q, r) = twice.divmod(&den);
+
+        if r < 0 { q - 1 } else { q }
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.den + &other.num * &self.den, &self.den * &other.den)
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.den - &other.num * &self.den, &self.den * &other.den)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.num, &self.den * &other.den)
+    }
+}
+
+impl Div for &Rational {
+    type Output = Rational;
+
+    fn div(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.den, &self.den * &other.num)
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational { num: -self.num.clone(), den: self.den.clone() }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        (&self.num * &other.den).partial_cmp(&(&other.num * &self.den))
+    }
+}
+
+pub type Vector = Vec<Rational>;
+
+pub fn to_vector(v: &[Int]) -> Vector {
+    v.iter().map(|n| Rational::from_int(n.clone())).collect()
+}
+
+pub fn dot(a: &[Rational], b: &[Rational]) -> Rational {
+    a.iter().zip(b.iter()).fold(Rational::zero(), |acc, (x, y)| &acc + &(x * y))
+}
+
+// a - c * b
+fn sub_scaled(a: &[Rational], b: &[Rational], c: &Rational) -> Vector {
+    a.iter().zip(b.iter()).map(|(x, y)| x - &(c * y)).collect()
+}
+
+// Gram-Schmidt orthogonalization: (b*, mu) with b*_i = b_i - sum mu_ij b*_j
+pub fn gram_schmidt(basis: &[Vector]) -> (Vec<Vector>, Vec<Vector>) {
+    let n = basis.len();
+    let mut ortho: Vec<Vector> = Vec::with_capacity(n);
+    let mut mu = vec![vec![Rational::zero(); n]; n];
+
+    for (i, b) in basis.iter().enumerate() {
+        let mut v = b.clone();
+
+        for j in 0..i {
+            mu[i][j] = &dot(b, &ortho[j]) / &dot(&ortho[j], &ortho[j]);
+            v = sub_scaled(&v, &ortho[j], &mu[i][j]);
+        }
+
+        ortho.push(v);
+    }
+
+    (ortho, mu)
+}
+
+// LLL reduce the (linearly independent) rows of basis with parameter delta,
+// usually 3/4 or a bit more
+pub fn lll(basis: &[Vector], delta: &Rational) -> Vec<Vector> {
+    let n = basis.len();
+    let mut b = basis.to_vec();
+
+    let (ortho, mut mu) = gram_schmidt(&b);
+    let mut norms: Vec<Rational> = ortho.iter().map(|v| dot(v, v)).collect();
+
+    let half = Rational::new(Int::from(1), Int::from(2));
+    let mut k = 1;
+
+    while k < n {
+        // size reduction
+        for j in (0..k).rev() {
+            if mu[k][j].abs() > half {
+                let q = Rational::from_int(mu[k][j].round());
+
+                b[k] = sub_scaled(&b[k], &b[j], &q);
+
+                let (lower, upper) = mu.split_at_mut(k);
+
+                for (x, y) in upper[0].iter_mut().zip(lower[j].iter()).take(j) {
+                    *x = &*x - &(&q * y);
+                }
+                upper[0][j] = &upper[0][j] - &q;
+            }
+        }
+
+        // Lovasz condition
+        let bound = &(delta - &(&mu[k][k - 1] * &mu[k][k - 1])) * &norms[k - 1];
+
+        if norms[k] >= bound {
+            k += 1;
+            continue;
+        }
+
+        // swap b_k and b_k-1 and fix up mu and the norms
+        let m = mu[k][k - 1].clone();
+        let norm = &norms[k] + &(&(&m * &m) * &norms[k - 1]);
+
+        mu[k][k - 1] = &(&m * &norms[k - 1]) / &norm;
+        norms[k] = &(&norms[k - 1] * &norms[k]) / &norm;
+        norms[k - 1] = norm;
+
+        b.swap(k, k - 1);
+
+        {
+            let (lower, upper) = mu.split_at_mut(k);
+
+            for (x, y) in upper[0].iter_mut().zip(lower[k - 1].iter_mut()).take(k - 1) {
+                std::mem::swap(x, y);
+            }
+        }
+
+        for i in k + 1..n {
+            let t = mu[i][k].clone();
+
+            mu[i][k] = &mu[i][k - 1] - &(&m * &t);
+            mu[i][k - 1] = &t + &(&mu[k][k - 1] * &mu[i][k]);
+        }
+
+        k = if k > 1 { k - 1 } else { 1 };
+    }
+
+    b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(num: i64, den: i64) -> Rational {
+        Rational::new(Int::from(num), Int::from(den))
+    }
+
+    fn basis(rows: &[&[i64]]) -> Vec<Vector> {
+        rows.iter().map(|row| row.iter().map(|n| r(*n, 1)).collect()).collect()
+    }
+
+    #[test]
+    fn test_rational() {
+        assert_eq!(r(2, 4), r(-1, -2));
+        assert_eq!(&r(1, 2) + &r(1, 3), r(5, 6));
+        assert_eq!(&r(1, 2) - &r(1, 3), r(1, 6));
+        assert_eq!(&r(2, 3) * &r(3, 4), r(1, 2));
+        assert_eq!(&r(2, 3) / &r(-4, 3), r(-1, 2));
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+
+        assert_eq!(r(7, 2).round(), 4);
+        assert_eq!(r(-7, 2).round(), -3);
+        assert_eq!(r(5, 3).round(), 2);
+        assert_eq!(r(-5, 3).round(), -2);
+        assert_eq!(r(4, 1).round(), 4);
+    }
+
+    #[test]
+    fn test_gram_schmidt() {
+        let b = basis(&[&[3, 1], &[2, 2]]);
+        let (ortho, mu) = gram_schmidt(&b);
+
+        assert_eq!(mu[1][0], r(4, 5));
+        assert_eq!(ortho[1], vec![r(-2, 5), r(6, 5)]);
+        assert!(dot(&ortho[0], &ortho[1]).is_zero());
+    }
+
+    #[test]
+    fn test_lll() {
+        // Example from https://en.wikipedia.org/wiki/Lenstra%E2%80%93Lenstra%E2%80%93Lov%C3%A1sz_lattice_basis_reduction_algorithm
+        let b = basis(&[&[1, 1, 1], &[-1, 0, 2], &[3, 5, 6]]);
+
+        assert_eq!(lll(&b, &r(3, 4)), basis(&[&[0, 1, 0], &[1, 0, 1], &[-1, 0, 2]]));
+
+        // Any LLL output has to be size reduced and satisfy the Lovasz
+        // condition
+        let b = basis(&[
+            &[-2, 0, 2, 0],
+            &[1, -1, -3, 0],
+            &[-2, 1, 1, 1],
+            &[1, 3, 1, 3],
+        ]);
+        let delta = r(99, 100);
+        let reduced = lll(&b, &delta);
+        let (ortho, mu) = gram_schmidt(&reduced);
+
+        for k in 1..reduced.len() {
+            assert!(mu[k][..k].iter().all(|m| m.abs() <= r(1, 2)));
+
+            let bound = &(&delta - &(&mu[k][k - 1] * &mu[k][k - 1])) * &dot(&ortho[k - 1], &ortho[k - 1]);
+            assert!(dot(&ortho[k], &ortho[k]) >= bound);
+        }
+
+        // Same lattice, so the same volume
+        let volume = |ortho: &[Vector]| ortho.iter().fold(r(1, 1), |acc, v| &acc * &dot(v, v));
+        assert_eq!(volume(&ortho), volume(&gram_schmidt(&b).0));
+    }
+}
@@ -0,0 +1,107 @@
+// Length extension attacks against secret-prefix MACs, i.e. MAC = H(key || msg)
+// for any Merkle-Damgard hash.
+//
+// Given H(key || msg) we can resume hashing from that digest, so for any
+// suffix we can compute H(key || msg || glue_padding || suffix) without
+// knowing the key. Only the key length needs to be guessed since it
+// determines the glue padding.
+
+use std::ops::Range;
+
+use crate::md::{Compression, MerkleDamgard, md_padding};
+use crate::md4::Md4Compression;
+use crate::sha1::Sha1Compression;
+use crate::sha256::Sha256Compression;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlg {
+    Sha1,
+    Md4,
+    Sha256,
+}
+
+// The padding the hash function appended to key || msg
+pub fn glue_padding<C: Compression>(compression: &C, key_len: usize, msg_len: usize) -> Vec<u8> {
+    md_padding(key_len + msg_len, compression.block_size(), compression.length_endian())
+}
+
+// Returns (forged message, forged MAC) for a single key length guess
+pub fn length_extend_with<C: Compression + Clone>(
+    compression: &C,
+    original_digest: &[u8],
+    original_msg: &[u8],
+    key_len: usize,
+    suffix: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut forged_msg = original_msg.to_vec();
+    forged_msg.extend_from_slice(&glue_padding(compression, key_len, original_msg.len()));
+
+    let processed_len = (key_len + forged_msg.len()) as u64;
+    let state = compression.state_from_bytes(original_digest);
+
+    let mut hasher = MerkleDamgard::with_state(compression.clone(), state, processed_len);
+    hasher.update(suffix);
+
+    forged_msg.extend_from_slice(suffix);
+
+    (forged_msg, hasher.finalize())
+}
+
+// Returns a (forged message, forged MAC) candidate for every key length
+// in key_len_range. The caller has to figure out which one is accepted.
+pub fn length_extend(
+    alg: HashAlg,
+    original_digest: &[u8],
+    original_msg: &[u8],
+    key_len_range: Range<usize>,
+    suffix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    key_len_range
+        .map(|key_len| match alg {
+            HashAlg::Sha1 => length_extend_with(&Sha1Compression, original_digest, original_msg, key_len, suffix),
+            HashAlg::Md4 => length_extend_with(&Md4Compression, original_digest, original_msg, key_len, suffix),
+            HashAlg::Sha256 => length_extend_with(&Sha256Compression, original_digest, original_msg, key_len, suffix),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::md_digest;
+    use crate::util::random_bytes;
+
+    fn secret_prefix_mac<C: Compression>(compression: C, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        let mut input = key.to_vec();
+        input.extend_from_slice(msg);
+
+        md_digest(compression, &input)
+    }
+
+    fn check_alg<C: Compression + Clone>(alg: HashAlg, compression: C) {
+        let key = random_bytes(13);
+        let msg = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+        let suffix = b";admin=true";
+
+        let mac = secret_prefix_mac(compression.clone(), &key, msg);
+
+        let candidates = length_extend(alg, &mac, msg, 0..32, suffix);
+        assert_eq!(candidates.len(), 32);
+
+        let valid: Vec<_> = candidates.iter()
+            .enumerate()
+            .filter(|(_, (forged_msg, forged_mac))| {
+                *forged_mac == secret_prefix_mac(compression.clone(), &key, forged_msg)
+            })
+            .map(|(key_len, _)| key_len)
+            .collect();
+
+        assert_eq!(valid, vec![13]);
+        assert!(candidates[13].0.ends_with(suffix));
+    }
+
+    #[test]
+    fn test_length_extend() {
+        check_alg(HashAlg::Sha1, Sha1Compression);
+        check_alg(HashAlg::Md4, Md4Compression);
+        check_alg(HashAlg::Sha256, Sha256Compression);
+    }
+}
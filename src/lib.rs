@@ -18,6 +18,38 @@ pub mod prime;
 pub mod asn1;
 pub mod rsa;
 pub mod dsa;
+pub mod bleichenbacher;
+pub mod ec;
+pub mod ecdsa;
+pub mod gf128;
+pub mod gcm;
+pub mod poly;
+pub mod md;
+pub mod sha256;
+pub mod length_extension;
+pub mod digest;
+pub mod hmac;
+pub mod cbc_mac;
+pub mod deflate;
+pub mod crime;
+pub mod toy_hash;
+pub mod multicollision;
+pub mod second_preimage;
+pub mod herding;
+pub mod md4_collision;
+pub mod rc4;
+pub mod rc4_bias;
+pub mod subgroup_confinement;
+pub mod kangaroo;
+pub mod ecdh;
+pub mod invalid_curve;
+pub mod montgomery;
+pub mod twist_attack;
+pub mod dsks;
+pub mod lattice;
+pub mod hnp;
+pub mod gf2;
+pub mod gcm_short_tag;
 
 #[macro_export]
 macro_rules! challenge_data {
@@ -0,0 +1,243 @@
+// Generic Merkle-Damgard construction
+// (https://en.wikipedia.org/wiki/Merkle–Damgård_construction)
+//
+// Hashes like SHA-1 and MD4 only differ in their compression function,
+// chaining state and how the message length is encoded in the padding.
+
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use std::cell::Cell;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthEndian {
+    Big,
+    Little,
+}
+
+pub trait Compression {
+    type State: Clone;
+
+    fn block_size(&self) -> usize;
+
+    fn length_endian(&self) -> LengthEndian;
+
+    fn initial_state(&self) -> Self::State;
+
+    fn compress(&self, state: &mut Self::State, block: &[u8]);
+
+    // The digest is the serialized final state
+    fn state_to_bytes(&self, state: &Self::State) -> Vec<u8>;
+
+    fn state_from_bytes(&self, bytes: &[u8]) -> Self::State;
+}
+
+// Padding for a message of msg_len bytes: the bit '1', enough '0' bits to
+// leave 64 bits free in the last block and then the message length in bits
+// as a 64-bit integer
+pub fn md_padding(msg_len: usize, block_size: usize, endian: LengthEndian) -> Vec<u8> {
+    let mut padding = vec![0x80u8];
+
+    let zeros = ((block_size - (msg_len + 1) % block_size) + block_size - 8) % block_size;
+    padding.extend_from_slice(&vec![0u8; zeros]);
+
+    let bit_len = (msg_len as u64).wrapping_mul(8);
+
+    match endian {
+        LengthEndian::Big => padding.write_u64::<BigEndian>(bit_len).unwrap(),
+        LengthEndian::Little => padding.write_u64::<LittleEndian>(bit_len).unwrap(),
+    }
+
+    padding
+}
+
+// Run the compression function over whole blocks without any padding
+pub fn md_iterate<C: Compression>(compression: &C, state: &C::State, data: &[u8]) -> C::State {
+    assert_eq!(data.len() % compression.block_size(), 0);
+
+    let mut state = state.clone();
+
+    for block in data.chunks(compression.block_size()) {
+        compression.compress(&mut state, block);
+    }
+
+    state
+}
+
+#[derive(Clone)]
+pub struct MerkleDamgard<C: Compression> {
+    compression: C,
+    state: C::State,
+    buffer: Vec<u8>,
+    // total number of bytes hashed so far, including the buffer
+    length: u64,
+}
+
+impl<C: Compression> MerkleDamgard<C> {
+    pub fn new(compression: C) -> Self {
+        let state = compression.initial_state();
+
+        MerkleDamgard::with_state(compression, state, 0)
+    }
+
+    // Resume hashing from an intermediate state after processed_len bytes
+    // (which must be a whole number of blocks) have already been hashed
+    pub fn with_state(compression: C, state: C::State, processed_len: u64) -> Self {
+        assert_eq!(processed_len % compression.block_size() as u64, 0);
+
+        MerkleDamgard { compression, state, buffer: Vec::new(), length: processed_len }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let block_size = self.compression.block_size();
+        let mut data = data;
+
+        self.length += data.len() as u64;
+
+        if !self.buffer.is_empty() {
+            let needed = block_size - self.buffer.len();
+
+            if data.len() < needed {
+                self.buffer.extend_from_slice(data);
+                return;
+            }
+
+            self.buffer.extend_from_slice(&data[..needed]);
+            self.compression.compress(&mut self.state, &self.buffer);
+            self.buffer.clear();
+
+            data = &data[needed..];
+        }
+
+        let mut blocks = data.chunks_exact(block_size);
+
+        for block in &mut blocks {
+            self.compression.compress(&mut self.state, block);
+        }
+
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    // The chaining state after the last complete block along with the
+    // number of bytes that went into it
+    pub fn export_state(&self) -> (C::State, u64) {
+        (self.state.clone(), self.length - self.buffer.len() as u64)
+    }
+
+    pub fn finalize(mut self) -> Vec<u8> {
+        let padding = md_padding(
+            self.length as usize,
+            self.compression.block_size(),
+            self.compression.length_endian());
+
+        // padding always ends on a block boundary so the buffer is flushed
+        let length = self.length;
+        self.update(&padding);
+        self.length = length;
+
+        assert!(self.buffer.is_empty());
+
+        self.compression.state_to_bytes(&self.state)
+    }
+}
+
+pub fn md_digest<C: Compression>(compression: C, data: &[u8]) -> Vec<u8> {
+    let mut hasher = MerkleDamgard::new(compression);
+    hasher.update(data);
+
+    hasher.finalize()
+}
+
+// Wraps a compression function to count how often it gets called, for
+// measuring the cost of attacks
+pub struct CountingCompression<'a, C: Compression> {
+    inner: &'a C,
+    calls: Cell<u64>,
+}
+
+impl<'a, C: Compression> CountingCompression<'a, C> {
+    pub fn new(inner: &'a C) -> Self {
+        CountingCompression { inner, calls: Cell::new(0) }
+    }
+
+    pub fn calls(&self) -> u64 {
+        self.calls.get()
+    }
+}
+
+impl<'a, C: Compression> Compression for CountingCompression<'a, C> {
+    type State = C::State;
+
+    fn block_size(&self) -> usize {
+        self.inner.block_size()
+    }
+
+    fn length_endian(&self) -> LengthEndian {
+        self.inner.length_endian()
+    }
+
+    fn initial_state(&self) -> C::State {
+        self.inner.initial_state()
+    }
+
+    fn compress(&self, state: &mut C::State, block: &[u8]) {
+        self.calls.set(self.calls.get() + 1);
+        self.inner.compress(state, block);
+    }
+
+    fn state_to_bytes(&self, state: &C::State) -> Vec<u8> {
+        self.inner.state_to_bytes(state)
+    }
+
+    fn state_from_bytes(&self, bytes: &[u8]) -> C::State {
+        self.inner.state_from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha1::{Sha1Compression, sha1_digest};
+    use crate::md4::Md4Compression;
+
+    #[test]
+    fn test_padding() {
+        for len in 0..200 {
+            let padding = md_padding(len, 64, LengthEndian::Big);
+
+            assert_eq!((len + padding.len()) % 64, 0);
+            assert!(padding.len() >= 9 && padding.len() <= 72);
+            assert_eq!(padding[padding.len() - 2..], [((len * 8) >> 8) as u8, (len * 8) as u8]);
+        }
+
+        let padding = md_padding(3, 64, LengthEndian::Little);
+        assert_eq!(padding[padding.len() - 8..], [24, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_streaming() {
+        let data: Vec<u8> = (0..300u32).map(|x| x as u8).collect();
+
+        for split in [0, 1, 63, 64, 65, 150, 300].iter() {
+            let mut hasher = MerkleDamgard::new(Sha1Compression);
+            hasher.update(&data[..*split]);
+            hasher.update(&data[*split..]);
+
+            assert_eq!(hasher.finalize(), sha1_digest(&data));
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        let data = [0x61u8; 200];
+
+        let mut hasher = MerkleDamgard::new(Md4Compression);
+        hasher.update(&data[..130]);
+
+        let (state, len) = hasher.export_state();
+        assert_eq!(len, 128);
+
+        let mut resumed = MerkleDamgard::with_state(Md4Compression, state, len);
+        resumed.update(&data[128..]);
+
+        assert_eq!(resumed.finalize(), md_digest(Md4Compression, &data));
+    }
+}
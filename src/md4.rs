@@ -1,96 +1,163 @@
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
+use crate::md::{Compression, LengthEndian, MerkleDamgard, md_digest};
+use crate::digest::Digest;
 
 pub const CHUNK_SIZE: usize = 64;
 pub const HASH_SIZE: usize = 16;
 
 // Implemented based on the RFC (https://tools.ietf.org/html/rfc1320)
 
-// NOTE: hasn't really been verified for correctness
+pub const ROUND_CONSTANTS: [u32; 3] = [0, 0x5a82_7999, 0x6ed9_eba1];
+pub const ROUND_SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+// Order the message words are used in within each round
+pub const MESSAGE_ORDER: [[usize; 16]; 3] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+    [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+];
+
+pub fn f(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+pub fn g(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+pub fn h(x: u32, y: u32, z: u32) -> u32 {
+    x ^ y ^ z
+}
+
+pub const ROUND_FUNCTIONS: [fn (u32, u32, u32) -> u32; 3] = [f, g, h];
+
+// Step i (0 to 15) of a round: a = (a + func(b, c, d) + x + constant) <<< shift
+pub fn step(round: usize, i: usize, a: u32, b: u32, c: u32, d: u32, x: u32) -> u32 {
+    a.wrapping_add(ROUND_FUNCTIONS[round](b, c, d))
+        .wrapping_add(x)
+        .wrapping_add(ROUND_CONSTANTS[round])
+        .rotate_left(ROUND_SHIFTS[round][i % 4])
+}
 
 pub fn md4_digest_chunk(abcd_in: &mut [u32; 4], chunk: &[u8]) {
     assert_eq!(chunk.len(), CHUNK_SIZE);
 
-    fn f(x: u32, y: u32, z: u32) -> u32 {
-        (x & y) | (!x & z)
-    }
+    let mut x = [0u32; 16];
 
-    fn g(x: u32, y: u32, z: u32) -> u32 {
-        (x & y) | (x & z) | (y & z)
+    let mut rdr = Cursor::new(chunk);
+    for xi in x.iter_mut() {
+        *xi = rdr.read_u32::<LittleEndian>().unwrap();
     }
 
-    fn h(x: u32, y: u32, z: u32) -> u32 {
-        x ^ y ^ z
-    }
+    let mut abcd = *abcd_in;
 
-    fn op(
-        func: fn (u32, u32, u32) -> u32,
-        abcd: &mut [u32; 4],
-        add_val: &[u32; 4],
-        constant: u32,
-        rot_val: &[u32; 4]) {
-        for (av, rv) in add_val.iter().zip(rot_val.iter()) {
-            abcd[0] = abcd[0].wrapping_add(func(abcd[1], abcd[2], abcd[3]))
-                .wrapping_add(*av)
-                .wrapping_add(constant)
-                .rotate_left(*rv);
+    for (round, order) in MESSAGE_ORDER.iter().enumerate() {
+        for (i, xi) in order.iter().enumerate() {
+            abcd[0] = step(round, i, abcd[0], abcd[1], abcd[2], abcd[3], x[*xi]);
 
             abcd.rotate_right(1);
         }
     }
 
-    let mut x = [0u32; 16];
+    for (val_in, val) in abcd_in.iter_mut().zip(abcd.iter()) {
+        *val_in = (*val_in).wrapping_add(*val);
+    }
+}
 
-    let mut rdr = Cursor::new(chunk);
-    for xi in x.iter_mut() {
-        *xi = rdr.read_u32::<LittleEndian>().unwrap();
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Md4Compression;
+
+impl Compression for Md4Compression {
+    type State = [u32; 4];
+
+    fn block_size(&self) -> usize {
+        CHUNK_SIZE
     }
 
-    let mut abcd = *abcd_in;
+    fn length_endian(&self) -> LengthEndian {
+        LengthEndian::Little
+    }
 
-    // Round 1
-    for i in (0..16).step_by(4) {
-        op(f, &mut abcd, &[x[i], x[i + 1], x[i + 2], x[i + 3]], 0, &[3, 7, 11, 19]);
+    fn initial_state(&self) -> [u32; 4] {
+        [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476]
     }
 
-    // round 2
-    for i in (0..16).step_by(4) {
-        op(g, &mut abcd, &[x[i], x[i + 1], x[i + 2], x[i + 3]], 0x5a82_7999, &[3, 5, 9, 13]);
+    fn compress(&self, state: &mut [u32; 4], block: &[u8]) {
+        md4_digest_chunk(state, block);
     }
 
-    // round 3
-    for i in (0..16).step_by(4) {
-        op(h, &mut abcd, &[x[i], x[i + 1], x[i + 2], x[i + 3]], 0x6ed9_eba1, &[3, 9, 11, 15]);
+    fn state_to_bytes(&self, state: &[u32; 4]) -> Vec<u8> {
+        let mut final_hash = Vec::new();
+
+        for val in state.iter() {
+            final_hash.write_u32::<LittleEndian>(*val).unwrap();
+        }
+
+        final_hash
     }
 
-    for (val_in, val) in abcd_in.iter_mut().zip(abcd.iter()) {
-        *val_in = (*val_in).wrapping_add(*val);
+    fn state_from_bytes(&self, bytes: &[u8]) -> [u32; 4] {
+        assert_eq!(bytes.len(), HASH_SIZE);
+
+        let mut rdr = Cursor::new(bytes);
+        let mut abcd = [0u32; 4];
+
+        for val in abcd.iter_mut() {
+            *val = rdr.read_u32::<LittleEndian>().unwrap();
+        }
+
+        abcd
     }
 }
 
-pub fn md4_digest(data: &[u8]) -> Vec<u8> {
-    let mut final_data = Vec::new();
-    final_data.extend_from_slice(&data);    
-    final_data.push(0x80);
+// Incremental MD4 hasher
+#[derive(Clone)]
+pub struct Md4 {
+    inner: MerkleDamgard<Md4Compression>,
+}
 
-    let padding = ((CHUNK_SIZE - final_data.len() % CHUNK_SIZE) + CHUNK_SIZE - 8) % CHUNK_SIZE;
+impl Md4 {
+    // Resume from an intermediate state abcd after processed_len bytes
+    pub fn with_state(abcd: [u32; 4], processed_len: u64) -> Self {
+        Md4 { inner: MerkleDamgard::with_state(Md4Compression, abcd, processed_len) }
+    }
+}
 
-    final_data.extend_from_slice(&vec![0u8; padding]);
-    final_data.write_u64::<LittleEndian>((data.len() * 8) as u64).unwrap();
+impl Digest for Md4 {
+    const BLOCK_SIZE: usize = CHUNK_SIZE;
+    const OUTPUT_SIZE: usize = HASH_SIZE;
 
-    assert_eq!(final_data.len() % CHUNK_SIZE, 0);
+    fn new() -> Self {
+        Md4 { inner: MerkleDamgard::new(Md4Compression) }
+    }
 
-    let mut abcd = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
 
-    for chunk in final_data.chunks(CHUNK_SIZE) {
-        md4_digest_chunk(&mut abcd, &chunk);
+    fn finalize(self) -> Vec<u8> {
+        self.inner.finalize()
     }
+}
 
-    let mut final_hash = Vec::new();
+pub fn md4_digest(data: &[u8]) -> Vec<u8> {
+    md_digest(Md4Compression, data)
+}
 
-    for val in abcd.iter() {
-        final_hash.write_u32::<LittleEndian>(*val).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::md4_digest;
+
+    // Test suite from the RFC
+    #[test]
+    fn test_md4_digest() {
+        assert_eq!(hex::encode(&md4_digest(b"")), "31d6cfe0d16ae931b73c59d7e0c089c0");
+        assert_eq!(hex::encode(&md4_digest(b"a")), "bde52cb31de33e46245e05fbdbd6fb24");
+        assert_eq!(hex::encode(&md4_digest(b"abc")), "a448017aaf21d8525fc10ae87aa6729d");
+        assert_eq!(hex::encode(&md4_digest(b"message digest")), "d9130a8164549fe818874806e1c7014b");
+        assert_eq!(
+            hex::encode(&md4_digest(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890")),
+            "e33b4ddc9c38f2199c3e7b164fcc0536");
     }
-
-    final_hash
 }
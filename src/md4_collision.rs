@@ -0,0 +1,319 @@
+// MD4 collisions (Wang et al., "Cryptanalysis of the Hash Functions MD4 and RIPEMD")
+// (https://link.springer.com/content/pdf/10.1007/11426639_1.pdf)
+//
+// The message difference M' = M + delta, with delta only in m1, m2 and m12,
+// cancels out inside the compression function if the intermediate states
+// satisfy a set of bit conditions. Conditions in round 1 can be forced by
+// choosing the message word that produces the state we want. The first
+// round 2 state, a5, is fixed by changing m0 and correcting m1..m4 so
+// round 1 stays intact. The rest is left to chance.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use rand::{self, Rng};
+use std::io::Cursor;
+
+use crate::md::Compression;
+use crate::md4::{Md4Compression, f, g, step, md4_digest_chunk, ROUND_CONSTANTS, ROUND_SHIFTS, MESSAGE_ORDER};
+
+// Bit numbers are the paper's, 1 being the least significant bit
+#[derive(Debug, Clone, Copy)]
+enum Cond {
+    Zero(u32),
+    One(u32),
+    // equal to the same bit of the state `back` steps earlier
+    Eq(u32, usize),
+    NotEq(u32, usize),
+}
+
+use self::Cond::*;
+
+// Conditions on the state after each step, step 0 being a1
+const CONDITIONS: [(usize, &[Cond]); 25] = [
+    // a1, d1, c1, b1
+    (0, &[Eq(7, 1)]),
+    (1, &[Zero(7), Eq(8, 1), Eq(11, 1)]),
+    (2, &[One(7), One(8), Zero(11), Eq(26, 1)]),
+    (3, &[One(7), Zero(8), Zero(11), Zero(26)]),
+    // a2, d2, c2, b2
+    (4, &[One(8), One(11), Zero(26), Eq(14, 1)]),
+    (5, &[Zero(14), Eq(19, 1), Eq(20, 1), Eq(21, 1), Eq(22, 1), One(26)]),
+    (6, &[Eq(13, 1), Zero(14), Eq(15, 1), Zero(19), Zero(20), One(21), Zero(22)]),
+    (7, &[One(13), One(14), Zero(15), Eq(17, 1), Zero(19), Zero(20), Zero(21), Zero(22)]),
+    // a3, d3, c3, b3
+    (8, &[One(13), One(14), One(15), Zero(17), Zero(19), Zero(20), Zero(21), One(22), Eq(23, 1), Eq(26, 1)]),
+    (9, &[One(13), One(14), One(15), Zero(17), Zero(20), One(21), One(22), Zero(23), One(26), Eq(30, 1)]),
+    (10, &[One(17), Zero(20), Zero(21), Zero(22), Zero(23), Zero(26), One(30), Eq(32, 1)]),
+    (11, &[Zero(20), One(21), One(22), Eq(23, 1), One(26), Zero(30), Zero(32)]),
+    // a4, d4, c4, b4
+    (12, &[Zero(23), Zero(26), Eq(27, 1), Eq(29, 1), One(30), Zero(32)]),
+    (13, &[Zero(23), Zero(26), One(27), One(29), Zero(30), One(32)]),
+    (14, &[Eq(19, 1), One(23), One(26), Zero(27), Zero(29), Zero(30)]),
+    (15, &[Zero(19), One(26), One(27), One(29), Zero(30)]),
+    // a5, d5, c5, b5
+    (16, &[Eq(19, 2), One(26), Zero(27), One(29), One(32)]),
+    (17, &[Eq(19, 1), Eq(26, 2), Eq(27, 2), Eq(29, 2), Eq(32, 2)]),
+    (18, &[Eq(26, 1), Eq(27, 1), Eq(29, 1), Eq(30, 1), Eq(32, 1)]),
+    (19, &[Eq(29, 1), One(30), Zero(32)]),
+    // a6, d6, c6
+    (20, &[One(29), One(32)]),
+    (21, &[Eq(29, 2)]),
+    (22, &[Eq(29, 1), NotEq(30, 1), NotEq(32, 1)]),
+    // b9, a10
+    (35, &[One(32)]),
+    (36, &[One(32)]),
+];
+
+const ROUND1_STEPS: usize = 16;
+const A5_STEP: usize = 16;
+
+fn mask(bit: u32) -> u32 {
+    1 << (bit - 1)
+}
+
+// The states in the order they are computed: a0, d0, c0, b0, a1, d1, ...
+// so the state after step i is at index i + 4
+fn md4_states(m: &[u32; 16]) -> [u32; 52] {
+    let iv = Md4Compression.initial_state();
+    let mut q = [0u32; 52];
+
+    q[..4].copy_from_slice(&[iv[0], iv[3], iv[2], iv[1]]);
+
+    for i in 0..48 {
+        let (round, j) = (i / 16, i % 16);
+
+        q[i + 4] = step(round, j, q[i], q[i + 3], q[i + 2], q[i + 1], m[MESSAGE_ORDER[round][j]]);
+    }
+
+    q
+}
+
+fn satisfied(cond: Cond, v: u32, q: &[u32], index: usize) -> bool {
+    match cond {
+        Zero(bit) => v & mask(bit) == 0,
+        One(bit) => v & mask(bit) != 0,
+        Eq(bit, back) => (v ^ q[index - back]) & mask(bit) == 0,
+        NotEq(bit, back) => (v ^ q[index - back]) & mask(bit) != 0,
+    }
+}
+
+// Set the bits of v (the state at q[index]) so the conditions hold
+fn enforce(conds: &[Cond], v: u32, q: &[u32], index: usize) -> u32 {
+    conds.iter().fold(v, |v, cond| {
+        match *cond {
+            Zero(bit) => v & !mask(bit),
+            One(bit) => v | mask(bit),
+            Eq(bit, back) => (v & !mask(bit)) | (q[index - back] & mask(bit)),
+            NotEq(bit, back) => (v & !mask(bit)) | (!q[index - back] & mask(bit)),
+        }
+    })
+}
+
+// The message word for round 1 step j that produces the state q[j + 4]
+fn solve_round1_word(j: usize, q: &[u32]) -> u32 {
+    q[j + 4].rotate_right(ROUND_SHIFTS[0][j % 4])
+        .wrapping_sub(q[j])
+        .wrapping_sub(f(q[j + 3], q[j + 2], q[j + 1]))
+}
+
+// Single-step modification: force every round 1 condition
+fn massage_round1(m: &mut [u32; 16]) {
+    let mut q = md4_states(m);
+
+    for (j, conds) in CONDITIONS.iter().take(ROUND1_STEPS) {
+        let v = step(0, *j, q[*j], q[j + 3], q[j + 2], q[j + 1], m[*j]);
+
+        q[j + 4] = enforce(conds, v, &q, j + 4);
+        m[*j] = solve_round1_word(*j, &q);
+    }
+}
+
+// Multi-step modification: a5 only depends on m0 and the round 1 states,
+// so pick m0 for the a5 we want and then re-solve m1..m4 to keep d1, c1,
+// b1 and a2 (and so everything after them) unchanged
+fn massage_a5(m: &mut [u32; 16]) {
+    let mut q = md4_states(m);
+    let index = A5_STEP + 4;
+
+    let a5 = enforce(CONDITIONS[A5_STEP].1, q[index], &q, index);
+
+    if a5 == q[index] {
+        return;
+    }
+
+    m[0] = a5.rotate_right(ROUND_SHIFTS[1][0])
+        .wrapping_sub(q[A5_STEP])
+        .wrapping_sub(g(q[A5_STEP + 3], q[A5_STEP + 2], q[A5_STEP + 1]))
+        .wrapping_sub(ROUND_CONSTANTS[1]);
+
+    q[4] = step(0, 0, q[0], q[3], q[2], q[1], m[0]);
+
+    for (j, word) in m.iter_mut().enumerate().take(5).skip(1) {
+        *word = solve_round1_word(j, &q);
+    }
+}
+
+pub fn massage_message(m: &mut [u32; 16]) {
+    massage_round1(m);
+    massage_a5(m);
+}
+
+// M' = M + delta
+pub fn apply_differential(m: &[u32; 16]) -> [u32; 16] {
+    let mut m2 = *m;
+
+    m2[1] = m2[1].wrapping_add(1 << 31);
+    m2[2] = m2[2].wrapping_add((1u32 << 31).wrapping_sub(1 << 28));
+    m2[12] = m2[12].wrapping_sub(1 << 16);
+
+    m2
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConditionReport {
+    pub round1_satisfied: usize,
+    pub round1_total: usize,
+    pub later_satisfied: usize,
+    pub later_total: usize,
+}
+
+pub fn check_conditions(m: &[u32; 16]) -> ConditionReport {
+    let q = md4_states(m);
+    let mut report = ConditionReport::default();
+
+    for (i, conds) in CONDITIONS.iter() {
+        let index = i + 4;
+        let count = conds.iter().filter(|cond| satisfied(**cond, q[index], &q, index)).count();
+
+        if *i < ROUND1_STEPS {
+            report.round1_satisfied += count;
+            report.round1_total += conds.len();
+        } else {
+            report.later_satisfied += count;
+            report.later_total += conds.len();
+        }
+    }
+
+    report
+}
+
+pub fn words_to_block(m: &[u32; 16]) -> Vec<u8> {
+    let mut block = Vec::with_capacity(64);
+
+    for word in m.iter() {
+        block.write_u32::<LittleEndian>(*word).unwrap();
+    }
+
+    block
+}
+
+pub fn block_to_words(block: &[u8]) -> [u32; 16] {
+    assert_eq!(block.len(), 64);
+
+    let mut rdr = Cursor::new(block);
+    let mut m = [0u32; 16];
+
+    for word in m.iter_mut() {
+        *word = rdr.read_u32::<LittleEndian>().unwrap();
+    }
+
+    m
+}
+
+// Search for two different 64 byte blocks with the same MD4 compression
+// output from the standard IV. report is called with the attempt number
+// and how many conditions the massaged message satisfies.
+pub fn find_collision_with<R: FnMut(u64, &ConditionReport)>(mut report: R) -> (Vec<u8>, Vec<u8>) {
+    let mut rng = rand::thread_rng();
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        let mut m = [0u32; 16];
+        for word in m.iter_mut() {
+            *word = rng.gen();
+        }
+
+        massage_message(&mut m);
+        report(attempts, &check_conditions(&m));
+
+        let m2 = apply_differential(&m);
+        let (block1, block2) = (words_to_block(&m), words_to_block(&m2));
+
+        let mut h1 = Md4Compression.initial_state();
+        let mut h2 = h1;
+        md4_digest_chunk(&mut h1, &block1);
+        md4_digest_chunk(&mut h2, &block2);
+
+        if h1 == h2 {
+            return (block1, block2);
+        }
+    }
+}
+
+pub fn find_collision() -> (Vec<u8>, Vec<u8>) {
+    find_collision_with(|_, _| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_massage() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let mut m = [0u32; 16];
+            for word in m.iter_mut() {
+                *word = rng.gen();
+            }
+
+            let report = check_conditions(&m);
+            assert!(report.round1_satisfied < report.round1_total);
+
+            massage_message(&mut m);
+
+            let report = check_conditions(&m);
+            assert_eq!(report.round1_satisfied, report.round1_total);
+
+            let q = md4_states(&m);
+            let a5 = A5_STEP + 4;
+            assert!(CONDITIONS[A5_STEP].1.iter().all(|cond| satisfied(*cond, q[a5], &q, a5)));
+        }
+    }
+
+    #[test]
+    fn test_differential() {
+        let m = block_to_words(&[0x5au8; 64]);
+        let m2 = apply_differential(&m);
+
+        assert_eq!(block_to_words(&words_to_block(&m)), m);
+
+        assert_eq!(m2[1].wrapping_sub(m[1]), 1 << 31);
+        assert_eq!(m2[2].wrapping_sub(m[2]), (1 << 31) - (1 << 28));
+        assert_eq!(m[12].wrapping_sub(m2[12]), 1 << 16);
+
+        for i in [0, 3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 14, 15].iter() {
+            assert_eq!(m[*i], m2[*i]);
+        }
+    }
+
+    #[test]
+    fn test_md4_states() {
+        // the last four states plus the IV are the compression output
+        let m = block_to_words(&[0u8; 64]);
+        let q = md4_states(&m);
+        let iv = Md4Compression.initial_state();
+
+        let mut h = iv;
+        md4_digest_chunk(&mut h, &words_to_block(&m));
+
+        assert_eq!(h, [
+            iv[0].wrapping_add(q[48]),
+            iv[1].wrapping_add(q[51]),
+            iv[2].wrapping_add(q[50]),
+            iv[3].wrapping_add(q[49]),
+        ]);
+    }
+}
@@ -0,0 +1,233 @@
+// Montgomery curves: B v^2 = u^3 + A u^2 + u (mod p)
+// The ladder only needs u coordinates, see "Montgomery curves and their
+// arithmetic" (https://eprint.iacr.org/2017/212.pdf)
+
+use ramp::int::{Int, RandomInt};
+use rand;
+
+use crate::ec::{ECCurve, ECPoint};
+use crate::ops::IntOpsExt;
+
+#[derive(Debug, Clone)]
+pub struct MontgomeryCurve {
+    pub a: Int,
+    pub b: Int,
+    pub p: Int,
+    // order of the subgroup generated by base_u and the curve's cofactor
+    pub order: Int,
+    pub cofactor: Int,
+    pub base_u: Int,
+}
+
+lazy_static! {
+    // The set 8 curve in Montgomery form, u = x - 178
+    pub static ref SET8_MONTGOMERY: MontgomeryCurve = MontgomeryCurve {
+        a: Int::from(534),
+        b: Int::from(1),
+        p: Int::from_str_radix("233970423115425145524320034830162017933", 10).unwrap(),
+        order: Int::from_str_radix("29246302889428143187362802287225875743", 10).unwrap(),
+        cofactor: Int::from(8),
+        base_u: Int::from(4),
+    };
+}
+
+impl MontgomeryCurve {
+    fn div(&self, n: &Int, d: &Int) -> Int {
+        (n * d.real_mod(&self.p).inv_mod(&self.p).unwrap()).real_mod(&self.p)
+    }
+
+    // v^2 for a given u
+    fn v_squared(&self, u: &Int) -> Int {
+        let p = &self.p;
+
+        self.div(&(u.pow(3) + &self.a * u * u + u).real_mod(p), &self.b)
+    }
+
+    pub fn v_from_u(&self, u: &Int) -> Option<Int> {
+        self.v_squared(u).sqrt_mod(&self.p)
+    }
+
+    // Any u is either on the curve or on its quadratic twist
+    pub fn is_on_curve(&self, u: &Int) -> bool {
+        self.v_from_u(u).is_some()
+    }
+
+    // u(k * P) from u(P), with 0 standing in for the point at infinity
+    pub fn ladder(&self, u: &Int, k: &Int) -> Int {
+        let p = &self.p;
+        let (mut u2, mut w2) = (Int::from(1), Int::from(0));
+        let (mut u3, mut w3) = (u.clone(), Int::from(1));
+
+        for i in (0..p.bit_length().max(k.bit_length())).rev() {
+            let bit = k.bit(i);
+
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+
+            let sum = (&u2 * &u3 - &w2 * &w3).real_mod(p);
+            let diff = (&u2 * &w3 - &w2 * &u3).real_mod(p);
+
+            u3 = (&sum * &sum) % p;
+            w3 = (u * &diff * &diff) % p;
+
+            let u2_sq = (&u2 * &u2) % p;
+            let w2_sq = (&w2 * &w2) % p;
+            let u2w2 = (&u2 * &w2) % p;
+            let new_u2 = (&u2_sq - &w2_sq).pow(2).real_mod(p);
+
+            w2 = (u2w2 * Int::from(4) * (u2_sq + &self.a * &u2 * &w2 + w2_sq)).real_mod(p);
+            u2 = new_u2;
+
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+        }
+
+        (u2 * w2.pow_mod(&(p - Int::from(2)), p)) % p
+    }
+
+    // u(2P) from u(P)
+    pub fn x_double(&self, u: &Int) -> Int {
+        let u_sq = (u * u) % &self.p;
+
+        self.div(&(&u_sq - Int::from(1)).pow(2), &(u * Int::from(4) * (&u_sq + &self.a * u + 1)))
+    }
+
+    // u(P + Q) from u(P), u(Q) and u(P - Q)
+    pub fn x_diff_add(&self, u_p: &Int, u_q: &Int, u_diff: &Int) -> Int {
+        self.div(&(u_p * u_q - Int::from(1)).pow(2), &(u_diff * (u_p - u_q).pow(2)))
+    }
+
+    // The curve and its twist together have 2p + 2 points
+    pub fn twist_order(&self) -> Int {
+        &self.p * Int::from(2) + 2 - &self.order * &self.cofactor
+    }
+
+    // A random u on the twist
+    pub fn random_twist_u(&self) -> Int {
+        let mut rng = rand::thread_rng();
+
+        loop {
+            let u = rng.gen_uint_below(&self.p);
+
+            if self.v_squared(&u) != 0 && !self.is_on_curve(&u) {
+                return u;
+            }
+        }
+    }
+
+    // A point on the twist whose order is the product of factors, each a
+    // distinct odd prime dividing the twist order exactly once
+    pub fn twist_point_of_order(&self, factors: &[u64]) -> Int {
+        let r = factors.iter().fold(Int::from(1), |acc, f| acc * Int::from(*f));
+        let cofactor = self.twist_order() / &r;
+
+        loop {
+            let u = self.ladder(&self.random_twist_u(), &cofactor);
+
+            if factors.iter().all(|f| self.ladder(&u, &(&r / Int::from(*f))) != 0) {
+                return u;
+            }
+        }
+    }
+
+    // The same curve in short Weierstrass form:
+    // x = (3u + A) / 3B, y = v / B, a = (3 - A^2) / 3B^2, b = (2A^3 - 9A) / 27B^3
+    pub fn to_weierstrass(&self) -> ECCurve {
+        let (a, b) = (&self.a, &self.b);
+        let base_v = self.v_from_u(&self.base_u).unwrap();
+
+        let mut curve = ECCurve {
+            a: self.div(&(Int::from(3) - a * a), &(b * b * Int::from(3))),
+            b: self.div(&(a.pow(3) * Int::from(2) - a * Int::from(9)), &(b.pow(3) * Int::from(27))),
+            p: self.p.clone(),
+            order: self.order.clone(),
+            base: ECPoint::Infinity,
+        };
+
+        curve.base = self.to_weierstrass_point(&ECPoint::new(self.base_u.clone(), base_v));
+
+        curve
+    }
+
+    // Points are (u, v) pairs in an ECPoint
+    pub fn to_weierstrass_point(&self, pt: &ECPoint) -> ECPoint {
+        match pt {
+            ECPoint::Infinity => ECPoint::Infinity,
+            ECPoint::Point { x: u, y: v } => ECPoint::Point {
+                x: self.div(&(u * Int::from(3) + &self.a), &(&self.b * Int::from(3))),
+                y: self.div(v, &self.b),
+            },
+        }
+    }
+
+    pub fn from_weierstrass_point(&self, pt: &ECPoint) -> ECPoint {
+        match pt {
+            ECPoint::Infinity => ECPoint::Infinity,
+            ECPoint::Point { x, y } => ECPoint::Point {
+                x: (&self.b * x - self.div(&self.a, &Int::from(3))).real_mod(&self.p),
+                y: (&self.b * y).real_mod(&self.p),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::SET8_CURVE;
+
+    #[test]
+    fn test_to_weierstrass() {
+        let curve = &*SET8_MONTGOMERY;
+        let weierstrass = curve.to_weierstrass();
+        let p = &curve.p;
+
+        assert_eq!(weierstrass.a, SET8_CURVE.a.real_mod(p));
+        assert_eq!(weierstrass.b, SET8_CURVE.b.real_mod(p));
+        assert!(SET8_CURVE.validate_point(&weierstrass.base));
+
+        assert_eq!(weierstrass.base.x(), Some(&Int::from(182)));
+        assert_eq!(curve.from_weierstrass_point(&weierstrass.base).x(), Some(&curve.base_u));
+    }
+
+    #[test]
+    fn test_ladder() {
+        let curve = &*SET8_MONTGOMERY;
+        let weierstrass = &*SET8_CURVE;
+        let u = &curve.base_u;
+
+        assert_eq!(curve.ladder(u, &curve.order), 0);
+        assert_eq!(curve.ladder(u, &Int::from(1)), *u);
+        assert_eq!(curve.ladder(u, &Int::from(2)), curve.x_double(u));
+
+        let u3 = curve.x_diff_add(&curve.x_double(u), u, u);
+        assert_eq!(curve.ladder(u, &Int::from(3)), u3);
+
+        // Same result as the Weierstrass double and add
+        let k = Int::from_str_radix("123456789123456789123456789", 10).unwrap();
+        let pt = curve.from_weierstrass_point(&weierstrass.scalar_mul_base(&k));
+
+        assert_eq!(pt.x(), Some(&curve.ladder(u, &k)));
+    }
+
+    #[test]
+    fn test_twist() {
+        let curve = &*SET8_MONTGOMERY;
+        let twist_order = curve.twist_order();
+
+        assert_eq!(twist_order, Int::from_str_radix("233970423115425145549737651362517029924", 10).unwrap());
+
+        let u = curve.random_twist_u();
+        assert!(!curve.is_on_curve(&u));
+        assert_eq!(curve.ladder(&u, &twist_order), 0);
+
+        let u = curve.twist_point_of_order(&[11, 107]);
+        assert_eq!(curve.ladder(&u, &Int::from(11 * 107)), 0);
+        assert!(curve.ladder(&u, &Int::from(11)) != 0);
+        assert!(curve.ladder(&u, &Int::from(107)) != 0);
+    }
+}
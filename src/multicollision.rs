@@ -0,0 +1,149 @@
+// Joux multicollisions (https://www.iacr.org/archive/crypto2004/31520306/multicollisions.pdf)
+//
+// Finding n single block collisions one after another, each starting from
+// the state the previous one ended in, gives 2^n messages that all hash to
+// the same value for the cost of n birthday searches. Concatenating a cheap
+// hash with an expensive one therefore costs barely more to break than the
+// expensive one alone.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::md::{Compression, md_digest};
+use crate::util::random_bytes;
+
+// Find blocks b1 and b2 with compress(state1, b1) == compress(state2, b2)
+// by a birthday search. Returns (b1, b2, resulting state). If the two
+// states are equal the blocks are distinct.
+pub fn find_state_collision<C>(compression: &C, state1: &C::State, state2: &C::State) -> (Vec<u8>, Vec<u8>, C::State)
+    where C: Compression, C::State: Eq + Hash {
+    let mut seen1: HashMap<C::State, Vec<u8>> = HashMap::new();
+    let mut seen2: HashMap<C::State, Vec<u8>> = HashMap::new();
+
+    loop {
+        let block = random_bytes(compression.block_size());
+
+        let mut h1 = state1.clone();
+        compression.compress(&mut h1, &block);
+
+        if let Some(other) = seen2.get(&h1) {
+            if *other != block {
+                return (block, other.clone(), h1);
+            }
+        }
+
+        let mut h2 = state2.clone();
+        compression.compress(&mut h2, &block);
+
+        if let Some(other) = seen1.get(&h2) {
+            if *other != block {
+                return (other.clone(), block, h2);
+            }
+        }
+
+        seen1.insert(h1, block.clone());
+        seen2.insert(h2, block);
+    }
+}
+
+pub fn find_block_collision<C>(compression: &C, state: &C::State) -> (Vec<u8>, Vec<u8>, C::State)
+    where C: Compression, C::State: Eq + Hash {
+    find_state_collision(compression, state, state)
+}
+
+// A chain of single block collisions. Picking either block of every pair
+// gives one of 2^n messages that all end in final_state.
+pub struct Multicollision<C: Compression> {
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    pub final_state: C::State,
+}
+
+impl<C> Multicollision<C> where C: Compression, C::State: Eq + Hash {
+    pub fn new(compression: &C, state: &C::State, n: usize) -> Self {
+        let mut multicollision = Multicollision { pairs: Vec::new(), final_state: state.clone() };
+
+        multicollision.extend(compression, n);
+
+        multicollision
+    }
+
+    // Add n more collisions, doubling the number of messages each time
+    pub fn extend(&mut self, compression: &C, n: usize) {
+        for _ in 0..n {
+            let (b1, b2, next) = find_block_collision(compression, &self.final_state);
+
+            self.pairs.push((b1, b2));
+            self.final_state = next;
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        1 << self.pairs.len()
+    }
+
+    // Bit i of index picks the block for the i-th pair
+    pub fn message(&self, index: usize) -> Vec<u8> {
+        self.pairs.iter()
+            .enumerate()
+            .flat_map(|(i, (b1, b2))| {
+                if index & (1 << i) == 0 { b1.iter() } else { b2.iter() }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+// Find two messages that collide under the concatenated hash f(m) || g(m)
+// where f is the cheaper of the two. Builds 2^(b/2) f-collisions, b being
+// the width of g, and looks for a g collision among them, adding another
+// f collision if there isn't one.
+pub fn cascade_collision<F, G>(f: &F, g: &G) -> (Vec<u8>, Vec<u8>)
+    where F: Compression, F::State: Eq + Hash, G: Compression + Clone {
+    let g_bits = g.state_to_bytes(&g.initial_state()).len() * 8;
+    let mut multicollision = Multicollision::new(f, &f.initial_state(), g_bits / 2);
+
+    loop {
+        let mut seen = HashMap::new();
+
+        for index in 0..multicollision.count() {
+            let msg = multicollision.message(index);
+            let digest = md_digest(g.clone(), &msg);
+
+            if let Some(other) = seen.insert(digest, index) {
+                return (multicollision.message(other), msg);
+            }
+        }
+
+        multicollision.extend(f, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toy_hash::{ToyCompression, toy_digest};
+
+    #[test]
+    fn test_multicollision() {
+        let f = ToyCompression::new(16);
+        let multicollision = Multicollision::new(&f, &f.initial_state(), 4);
+
+        let digest = toy_digest(16, &multicollision.message(0));
+
+        for index in 1..multicollision.count() {
+            let msg = multicollision.message(index);
+
+            assert_ne!(msg, multicollision.message(0));
+            assert_eq!(toy_digest(16, &msg), digest);
+        }
+    }
+
+    #[test]
+    fn test_cascade_collision() {
+        let (m1, m2) = cascade_collision(&ToyCompression::new(16), &ToyCompression::new(24));
+
+        assert_ne!(m1, m2);
+        assert_eq!(toy_digest(16, &m1), toy_digest(16, &m2));
+        assert_eq!(toy_digest(24, &m1), toy_digest(24, &m2));
+    }
+}
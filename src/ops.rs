@@ -39,11 +39,17 @@ pub fn hamming_dist_str(a: String, b: String) -> Result<usize, ()> {
 pub trait IntOpsExt {
 	fn inv_mod(&self, n: &Int) -> Option<Int>;
 
+	fn real_mod(&self, n: &Int) -> Int;
+
 	fn nth_root(&self, n: usize) -> Int;	
 
 	fn to_bytes(&self) -> Vec<u8>;
 
+	fn to_bytes_len(&self, len: usize) -> Vec<u8>;
+
 	fn from_bytes(bytes: &[u8]) -> Int;
+
+	fn sqrt_mod(&self, p: &Int) -> Option<Int>;
 }
 
 impl IntOpsExt for Int {
@@ -74,15 +80,34 @@ impl IntOpsExt for Int {
 		}
 	}
 
+	// % returns negative values for negative inputs, this always
+	// returns a value in [0, n)
+	fn real_mod(&self, n: &Int) -> Int {
+		let result = self % n;
+
+		if result < 0 {
+			result + n
+		} else {
+			result
+		}
+	}
+
+	// Newton's method from above. Stops as soon as the estimate stops
+	// going down, since it can bounce between the floor of the root and
+	// one more than it (e.g. 63 and 64 for 4095).
 	fn nth_root(&self, n: usize) -> Int {
-		let mut x = self / &Int::from(2);
-		let mut x_prev = Int::from(0);
-		while x != x_prev {
-			x_prev = x.clone();
-			x = (&x*(n - 1) + self / (x.pow(n - 1))) / &Int::from(n);
+		if self < &Int::from(2) {
+			return self.clone();
 		}
 
-		x
+		let mut x = self / &Int::from(2);
+		loop {
+			let next = (&x*(n - 1) + self / (x.pow(n - 1))) / &Int::from(n);
+			if next >= x {
+				return x;
+			}
+			x = next;
+		}
 	}
 
 	// XXX dumb hack to work around ramp not having to/from_bytes methods
@@ -96,14 +121,140 @@ impl IntOpsExt for Int {
 		hex::decode(&hex_encoded).unwrap()
 	}
 
+	// Same as to_bytes but left pads with zeros to the requested length
+	fn to_bytes_len(&self, len: usize) -> Vec<u8> {
+		let bytes = self.to_bytes();
+		assert!(bytes.len() <= len);
+
+		let mut padded = vec![0u8; len - bytes.len()];
+		padded.extend_from_slice(&bytes);
+
+		padded
+	}
+
 	fn from_bytes(bytes: &[u8]) -> Int {
 		Int::from_str_radix(&hex::encode(bytes), 16).unwrap()
 	}
+
+	// Square root modulo an odd prime p with Tonelli-Shanks
+	// (https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm)
+	// None if self isn't a quadratic residue
+	fn sqrt_mod(&self, p: &Int) -> Option<Int> {
+		let n = self.real_mod(p);
+		let p_minus_1 = p - Int::from(1);
+		let euler = &p_minus_1 / 2;
+
+		if n == 0 {
+			return Some(n);
+		}
+
+		if n.pow_mod(&euler, p) != 1 {
+			return None;
+		}
+
+		// p - 1 = q * 2^s with q odd
+		let s = p_minus_1.trailing_zeros();
+		let q = p_minus_1.clone() >> s as usize;
+
+		let mut z = Int::from(2);
+		while z.pow_mod(&euler, p) != p_minus_1 {
+			z += 1;
+		}
+
+		let mut m = s;
+		let mut c = z.pow_mod(&q, p);
+		let mut t = n.pow_mod(&q, p);
+		let mut r = n.pow_mod(&((&q + 1) / 2), p);
+
+		while t != 1 {
+			// least i with t^(2^i) = 1
+			let mut i = 0;
+			let mut t2i = t.clone();
+			while t2i != 1 {
+				t2i = (&t2i * &t2i) % p;
+				i += 1;
+			}
+
+			let b = c.pow_mod(&(Int::from(1) << (m - i - 1) as usize), p);
+
+			m = i;
+			c = (&b * &b) % p;
+			t = (t * &c) % p;
+			r = (r * b) % p;
+		}
+
+		Some(r)
+	}
+}
+
+// Chinese remainder theorem: given (residue, modulus) pairs with pairwise
+// coprime moduli, returns (x, product of the moduli) with x congruent to
+// every residue
+pub fn crt(residues: &[(Int, Int)]) -> (Int, Int) {
+	let modulus = residues.iter().fold(Int::from(1), |acc, (_, m)| acc * m);
+	let mut x = Int::from(0);
+
+	for (a, m) in residues.iter() {
+		let rest = &modulus / m;
+		let inv = rest.inv_mod(m).expect("moduli are not coprime");
+
+		x = (x + a * &rest * inv) % &modulus;
+	}
+
+	(x, modulus)
 }
 
 #[cfg(test)]
 mod tests {
+    use ramp::int::Int;
+
     #[test]
     fn test_inv_mod() {
     }
+
+    #[test]
+    fn test_crt() {
+        let residues = [(Int::from(2), Int::from(3)), (Int::from(3), Int::from(5)), (Int::from(2), Int::from(7))];
+
+        assert_eq!(super::crt(&residues), (Int::from(23), Int::from(105)));
+    }
+
+    #[test]
+    fn test_nth_root() {
+        use super::IntOpsExt;
+
+        for (n, k, root) in [(4095, 2, 63), (4096, 2, 64), (3, 2, 1), (1, 2, 1), (26, 3, 2), (27, 3, 3), (1_000_000, 3, 100)].iter() {
+            assert_eq!(Int::from(*n).nth_root(*k), Int::from(*root));
+        }
+
+        let big = Int::from_str_radix("98765432109876543210", 10).unwrap();
+        let cube = big.pow(3);
+        assert_eq!(cube.nth_root(3), big);
+        assert_eq!((cube - Int::from(1)).nth_root(3), &big - Int::from(1));
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        use super::IntOpsExt;
+
+        // p = 1 mod 4 needs the full algorithm
+        let p = Int::from_str_radix("233970423115425145524320034830162017933", 10).unwrap();
+
+        for n in [2, 3, 5, 1234567, 1 << 30].iter() {
+            let n = Int::from(*n);
+
+            match n.sqrt_mod(&p) {
+                Some(r) => assert_eq!((&r * &r) % &p, n),
+                None => assert_eq!(n.pow_mod(&((&p - 1) / 2), &p), &p - 1),
+            }
+        }
+
+        let r = Int::from_str_radix("98765432109876543210", 10).unwrap();
+        let root = ((&r * &r) % &p).sqrt_mod(&p).unwrap();
+        assert!(root == r || root == &p - &r);
+
+        assert_eq!(Int::from(0).sqrt_mod(&p), Some(Int::from(0)));
+        let root = Int::from(10).sqrt_mod(&Int::from(13)).unwrap();
+        assert!(root == 6 || root == 7);
+    }
 }
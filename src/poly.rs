@@ -0,0 +1,486 @@
+// Polynomials with coefficients in GF(2^128) and factorization over that
+// field, following https://en.wikipedia.org/wiki/Factorization_of_polynomials_over_finite_fields
+
+use std::ops::{Add, Mul};
+
+use crate::gf128::GF128;
+use crate::util::random_bytes;
+
+// coeffs[i] is the coefficient of X^i. There are never any trailing zero
+// coefficients so the zero polynomial has no coefficients at all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Poly {
+    coeffs: Vec<GF128>,
+}
+
+impl Poly {
+    pub fn new(coeffs: Vec<GF128>) -> Self {
+        let mut poly = Poly { coeffs };
+        poly.normalize();
+
+        poly
+    }
+
+    pub fn zero() -> Self {
+        Poly { coeffs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Poly::constant(GF128::one())
+    }
+
+    pub fn constant(c: GF128) -> Self {
+        Poly::new(vec![c])
+    }
+
+    // The polynomial X
+    pub fn x() -> Self {
+        Poly::new(vec![GF128::zero(), GF128::one()])
+    }
+
+    pub fn random(degree: usize) -> Self {
+        let coeffs = (0..=degree)
+            .map(|_| GF128::from_bytes(&random_bytes(16)))
+            .collect();
+
+        Poly::new(coeffs)
+    }
+
+    fn normalize(&mut self) {
+        while let Some(c) = self.coeffs.last() {
+            if c.is_zero() {
+                self.coeffs.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn coeffs(&self) -> &[GF128] {
+        &self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        *self == Poly::one()
+    }
+
+    // The degree of the zero polynomial is treated as 0
+    pub fn degree(&self) -> usize {
+        if self.coeffs.is_empty() {
+            0
+        } else {
+            self.coeffs.len() - 1
+        }
+    }
+
+    pub fn leading_coeff(&self) -> GF128 {
+        match self.coeffs.last() {
+            Some(c) => *c,
+            None => GF128::zero(),
+        }
+    }
+
+    pub fn scale(&self, c: GF128) -> Poly {
+        Poly::new(self.coeffs.iter().map(|x| *x * c).collect())
+    }
+
+    pub fn monic(&self) -> Poly {
+        match self.leading_coeff().inverse() {
+            Some(inv) => self.scale(inv),
+            None => Poly::zero(),
+        }
+    }
+
+    pub fn eval(&self, x: GF128) -> GF128 {
+        self.coeffs.iter().rev().fold(GF128::zero(), |acc, c| acc * x + *c)
+    }
+
+    pub fn divmod(&self, divisor: &Poly) -> (Poly, Poly) {
+        assert!(!divisor.is_zero());
+
+        if self.coeffs.len() < divisor.coeffs.len() {
+            return (Poly::zero(), self.clone());
+        }
+
+        let lead_inv = divisor.leading_coeff().inverse().unwrap();
+        let d_deg = divisor.degree();
+
+        let mut rem = self.coeffs.clone();
+        let mut quot = vec![GF128::zero(); self.coeffs.len() - d_deg];
+
+        for i in (0..quot.len()).rev() {
+            let factor = rem[i + d_deg] * lead_inv;
+
+            if factor.is_zero() {
+                continue;
+            }
+
+            quot[i] = factor;
+
+            for (j, c) in divisor.coeffs.iter().enumerate() {
+                rem[i + j] += *c * factor;
+            }
+        }
+
+        (Poly::new(quot), Poly::new(rem))
+    }
+
+    pub fn rem(&self, divisor: &Poly) -> Poly {
+        self.divmod(divisor).1
+    }
+
+    pub fn div(&self, divisor: &Poly) -> Poly {
+        self.divmod(divisor).0
+    }
+
+    pub fn mul_mod(&self, other: &Poly, modulus: &Poly) -> Poly {
+        (self * other).rem(modulus)
+    }
+
+    pub fn pow_mod(&self, exp: u128, modulus: &Poly) -> Poly {
+        let base = self.rem(modulus);
+        let mut result = Poly::one().rem(modulus);
+
+        for i in (0..128).rev() {
+            result = result.mul_mod(&result, modulus);
+
+            if (exp >> i) & 1 == 1 {
+                result = result.mul_mod(&base, modulus);
+            }
+        }
+
+        result
+    }
+
+    // self^(2^k) mod modulus
+    pub fn pow_2k_mod(&self, k: usize, modulus: &Poly) -> Poly {
+        let mut result = self.rem(modulus);
+
+        for _ in 0..k {
+            result = result.mul_mod(&result, modulus);
+        }
+
+        result
+    }
+
+    pub fn derivative(&self) -> Poly {
+        // In characteristic 2 the even powers vanish
+        let coeffs = self.coeffs.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| if i % 2 == 1 { *c } else { GF128::zero() })
+            .collect();
+
+        Poly::new(coeffs)
+    }
+
+    // Square root of a polynomial that only has even powers of X
+    pub fn sqrt(&self) -> Poly {
+        let coeffs = self.coeffs.iter()
+            .step_by(2)
+            .map(|c| c.sqrt())
+            .collect();
+
+        Poly::new(coeffs)
+    }
+}
+
+impl<'a, 'b> Add<&'b Poly> for &'a Poly {
+    type Output = Poly;
+
+    fn add(self, other: &'b Poly) -> Poly {
+        let len = std::cmp::max(self.coeffs.len(), other.coeffs.len());
+
+        let coeffs = (0..len)
+            .map(|i| {
+                let a = self.coeffs.get(i).cloned().unwrap_or_default();
+                let b = other.coeffs.get(i).cloned().unwrap_or_default();
+
+                a + b
+            })
+            .collect();
+
+        Poly::new(coeffs)
+    }
+}
+
+impl Add for Poly {
+    type Output = Poly;
+
+    fn add(self, other: Poly) -> Poly {
+        &self + &other
+    }
+}
+
+impl<'a, 'b> Mul<&'b Poly> for &'a Poly {
+    type Output = Poly;
+
+    fn mul(self, other: &'b Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+
+        let mut coeffs = vec![GF128::zero(); self.coeffs.len() + other.coeffs.len() - 1];
+
+        for (i, a) in self.coeffs.iter().enumerate() {
+            if a.is_zero() {
+                continue;
+            }
+
+            for (j, b) in other.coeffs.iter().enumerate() {
+                coeffs[i + j] += *a * *b;
+            }
+        }
+
+        Poly::new(coeffs)
+    }
+}
+
+impl Mul for Poly {
+    type Output = Poly;
+
+    fn mul(self, other: Poly) -> Poly {
+        &self * &other
+    }
+}
+
+// Returns the monic gcd
+pub fn gcd(a: &Poly, b: &Poly) -> Poly {
+    let mut a = a.clone();
+    let mut b = b.clone();
+
+    while !b.is_zero() {
+        let r = a.rem(&b);
+        a = b;
+        b = r;
+    }
+
+    a.monic()
+}
+
+// Returns (factor, multiplicity) pairs where each factor is square-free
+pub fn square_free_factorization(f: &Poly) -> Vec<(Poly, usize)> {
+    let f = f.monic();
+    let mut factors = Vec::new();
+
+    let mut c = gcd(&f, &f.derivative());
+    let mut w = f.div(&c);
+    let mut i = 1;
+
+    while !w.is_one() {
+        let y = gcd(&w, &c);
+        let fac = w.div(&y);
+
+        if !fac.is_one() {
+            factors.push((fac, i));
+        }
+
+        w = y;
+        c = c.div(&w);
+        i += 1;
+    }
+
+    // Whatever is left is a perfect square (p = 2)
+    if !c.is_one() {
+        for (fac, mult) in square_free_factorization(&c.sqrt()) {
+            factors.push((fac, mult * 2));
+        }
+    }
+
+    factors
+}
+
+// Splits a square-free monic polynomial into (factor, degree) pairs where
+// each factor is the product of all the irreducible factors of that degree
+pub fn distinct_degree_factorization(f: &Poly) -> Vec<(Poly, usize)> {
+    let mut factors = Vec::new();
+    let mut f_star = f.monic();
+    let mut h = Poly::x().rem(&f_star);
+    let mut i = 1;
+
+    while f_star.degree() >= 2 * i {
+        // h = X^(q^i) mod f*
+        h = h.pow_2k_mod(128, &f_star);
+
+        let g = gcd(&f_star, &(&h + &Poly::x()));
+
+        if !g.is_one() {
+            f_star = f_star.div(&g);
+            h = h.rem(&f_star);
+            factors.push((g, i));
+        }
+
+        i += 1;
+    }
+
+    if f_star.degree() > 0 {
+        let degree = f_star.degree();
+        factors.push((f_star, degree));
+    }
+
+    factors
+}
+
+// Cantor-Zassenhaus for characteristic 2. Instead of raising a random
+// polynomial to (q^d - 1) / 2 this uses the trace map
+// a + a^2 + a^4 + ... + a^(2^(128d - 1)) which is 0 or 1 modulo each
+// irreducible factor with roughly equal probability.
+pub fn equal_degree_factorization(f: &Poly, d: usize) -> Vec<Poly> {
+    let f = f.monic();
+    let n = f.degree();
+
+    assert!(d > 0);
+    assert_eq!(n % d, 0);
+
+    let r = n / d;
+    let mut factors = vec![f.clone()];
+
+    while factors.len() < r {
+        let a = Poly::random(n - 1).rem(&f);
+
+        let mut trace = a.clone();
+        let mut term = a;
+        for _ in 1..(128 * d) {
+            term = term.mul_mod(&term, &f);
+            trace = &trace + &term;
+        }
+
+        let mut new_factors = Vec::new();
+
+        for u in factors.iter() {
+            if u.degree() == d {
+                new_factors.push(u.clone());
+                continue;
+            }
+
+            let g = gcd(u, &trace);
+
+            if !g.is_one() && g != *u {
+                new_factors.push(u.div(&g));
+                new_factors.push(g);
+            } else {
+                new_factors.push(u.clone());
+            }
+        }
+
+        factors = new_factors;
+    }
+
+    factors
+}
+
+pub fn factor(f: &Poly) -> Vec<(Poly, usize)> {
+    let mut factors = Vec::new();
+
+    for (sf, mult) in square_free_factorization(f) {
+        for (ddf, d) in distinct_degree_factorization(&sf) {
+            for irreducible in equal_degree_factorization(&ddf, d) {
+                factors.push((irreducible, mult));
+            }
+        }
+    }
+
+    factors
+}
+
+// Returns the distinct roots of f
+pub fn find_roots(f: &Poly) -> Vec<GF128> {
+    let mut roots = Vec::new();
+
+    if f.degree() == 0 {
+        return roots;
+    }
+
+    for (sf, _) in square_free_factorization(f) {
+        for (ddf, d) in distinct_degree_factorization(&sf) {
+            if d != 1 {
+                continue;
+            }
+
+            for linear in equal_degree_factorization(&ddf, 1) {
+                // X + c has the root c
+                let root = linear.coeffs()[0];
+
+                if !roots.contains(&root) {
+                    roots.push(root);
+                }
+            }
+        }
+    }
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_elem() -> GF128 {
+        GF128::from_bytes(&random_bytes(16))
+    }
+
+    fn linear(root: GF128) -> Poly {
+        Poly::new(vec![root, GF128::one()])
+    }
+
+    #[test]
+    fn test_divmod() {
+        let a = Poly::random(7);
+        let b = Poly::random(3);
+
+        let (q, r) = a.divmod(&b);
+
+        assert!(r.degree() < b.degree());
+        assert_eq!(&(&q * &b) + &r, a);
+    }
+
+    #[test]
+    fn test_gcd() {
+        let common = &linear(random_elem()) * &linear(random_elem());
+        let a = &common * &Poly::random(3);
+        let b = &common * &Poly::random(2);
+
+        assert_eq!(gcd(&a, &b).rem(&common.monic()), Poly::zero());
+    }
+
+    #[test]
+    fn test_find_roots() {
+        let roots = [random_elem(), random_elem(), random_elem()];
+
+        // (X - r0)^2 (X - r1) (X - r2) (irreducible quadratic or product of two linears)
+        let mut f = &linear(roots[0]) * &linear(roots[0]);
+        f = &f * &linear(roots[1]);
+        f = &f * &linear(roots[2]);
+        f = &f * &Poly::random(2);
+
+        let found = find_roots(&f);
+
+        for root in roots.iter() {
+            assert!(found.contains(root));
+            assert!(f.eval(*root).is_zero());
+        }
+
+        for root in found.iter() {
+            assert!(f.eval(*root).is_zero());
+        }
+    }
+
+    #[test]
+    fn test_square_free() {
+        let a = linear(random_elem());
+        let b = linear(random_elem());
+
+        let f = &(&(&a * &a) * &(&a * &b)) * &b;
+
+        let mut factors = square_free_factorization(&f);
+        factors.sort_by_key(|(_, mult)| *mult);
+
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[0], (b, 2));
+        assert_eq!(factors[1], (a, 3));
+    }
+}
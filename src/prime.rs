@@ -43,6 +43,52 @@ pub fn test_prime(n: &Int, k: usize) -> bool {
     return true;
 }
 
+// All primes below bound (sieve of Eratosthenes)
+pub fn small_primes(bound: u64) -> Vec<u64> {
+    let mut is_prime = vec![true; bound as usize];
+    let mut primes = Vec::new();
+
+    for i in 2..bound as usize {
+        if !is_prime[i] {
+            continue;
+        }
+
+        primes.push(i as u64);
+
+        for j in (i * i..bound as usize).step_by(i) {
+            is_prime[j] = false;
+        }
+    }
+
+    primes
+}
+
+// The distinct prime factors of n below bound, by trial division
+pub fn factor_small(n: &Int, bound: u64) -> Vec<u64> {
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+
+    for d in 2..bound {
+        if n == 1 {
+            break;
+        }
+
+        let divisor = Int::from(d);
+
+        if &n % &divisor != 0 {
+            continue;
+        }
+
+        factors.push(d);
+
+        while &n % &divisor == 0 {
+            n /= &divisor;
+        }
+    }
+
+    factors
+}
+
 pub fn gen_prime_with_seed(seed: &Int) -> Int {
     let mut test_num = seed.clone();
 
@@ -97,4 +143,19 @@ mod tests {
             super::test_prime(&some_composite, 100),
             false);
     }
+
+    #[test]
+    fn test_factor_small() {
+        // 2^3 * 3 * 5^2 * 101 * a prime above the bound
+        let n = Int::from(2 * 2 * 2 * 3 * 5 * 5 * 101) * Int::from(1_000_003);
+
+        assert_eq!(super::factor_small(&n, 1000), vec![2, 3, 5, 101]);
+        assert_eq!(super::factor_small(&Int::from(97), 50), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_small_primes() {
+        assert_eq!(super::small_primes(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(super::small_primes(1 << 16).len(), 6542);
+    }
 }
@@ -0,0 +1,69 @@
+// RC4 (https://en.wikipedia.org/wiki/RC4)
+
+#[derive(Clone)]
+pub struct Rc4 {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    // Key scheduling
+    pub fn new(key: &[u8]) -> Self {
+        assert!(!key.is_empty() && key.len() <= 256);
+
+        let mut s = [0u8; 256];
+        for (i, v) in s.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, j as usize);
+        }
+
+        Rc4 { s, i: 0, j: 0 }
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.s[self.i as usize]);
+        self.s.swap(self.i as usize, self.j as usize);
+
+        self.s[self.s[self.i as usize].wrapping_add(self.s[self.j as usize]) as usize]
+    }
+
+    pub fn keystream(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_byte()).collect()
+    }
+
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        for b in data.iter_mut() {
+            *b ^= self.next_byte();
+        }
+    }
+}
+
+pub fn rc4_crypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut output = data.to_vec();
+
+    Rc4::new(key).apply_keystream(&mut output);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc4() {
+        assert_eq!(hex::encode(&rc4_crypt(b"Key", b"Plaintext")), "bbf316e8d940af0ad3");
+        assert_eq!(hex::encode(&rc4_crypt(b"Wiki", b"pedia")), "1021bf0420");
+        assert_eq!(hex::encode(&rc4_crypt(b"Secret", b"Attack at dawn")), "45a01f645fc35b383552544b9bf5");
+
+        assert_eq!(hex::encode(&Rc4::new(b"Key").keystream(10)), "eb9f7781b734ca72a719");
+        assert_eq!(rc4_crypt(b"Key", &rc4_crypt(b"Key", b"Plaintext")), b"Plaintext");
+    }
+}
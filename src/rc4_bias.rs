@@ -0,0 +1,187 @@
+// RC4 single-byte biases (AlFardan et al., "On the Security of RC4 in TLS",
+// http://www.isg.rhul.ac.uk/tls/RC4biases.pdf)
+//
+// The RC4 keystream isn't uniform: byte 16 is 240 and byte 32 is 224 a
+// little more often than they should be. If the same plaintext is encrypted
+// under enough different keys, the most common ciphertext byte at those
+// positions gives the plaintext byte away. Padding the request moves each
+// cookie byte onto a biased position in turn.
+
+use rand::{self, Rng};
+use std::sync::Arc;
+use std::thread;
+
+use crate::rc4::rc4_crypt;
+
+pub const COOKIE: &str = "QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F";
+
+// (0-indexed keystream position, value it's biased towards)
+pub const BIASES: [(usize, u8); 2] = [(15, 240), (31, 224)];
+
+pub trait Rc4Oracle {
+    fn encrypt(&self, request: &[u8]) -> Vec<u8>;
+}
+
+#[derive(Clone)]
+pub struct CookieOracle {
+    cookie: Vec<u8>,
+}
+
+impl CookieOracle {
+    pub fn new(cookie: &[u8]) -> Self {
+        CookieOracle { cookie: cookie.to_vec() }
+    }
+}
+
+impl Rc4Oracle for CookieOracle {
+    // a fresh 128 bit key for every request
+    fn encrypt(&self, request: &[u8]) -> Vec<u8> {
+        let key: [u8; 16] = rand::thread_rng().gen();
+
+        let mut plaintext = request.to_vec();
+        plaintext.extend_from_slice(&self.cookie);
+
+        rc4_crypt(&key, &plaintext)
+    }
+}
+
+type Counts = [[u64; 256]; BIASES.len()];
+
+// Histogram of the ciphertext bytes at the biased positions
+fn sample<O: Rc4Oracle>(oracle: &O, request: &[u8], samples: u64) -> Counts {
+    let mut counts = [[0u64; 256]; BIASES.len()];
+
+    for _ in 0..samples {
+        let ciphertext = oracle.encrypt(request);
+
+        for (count, (pos, _)) in counts.iter_mut().zip(BIASES.iter()) {
+            if let Some(c) = ciphertext.get(*pos) {
+                count[*c as usize] += 1;
+            }
+        }
+    }
+
+    counts
+}
+
+fn sample_parallel<O>(oracle: &Arc<O>, request: &[u8], samples: u64, threads: u64) -> Counts
+    where O: Rc4Oracle + Send + Sync + 'static {
+    let handles: Vec<_> = (0..threads).map(|t| {
+        let oracle = Arc::clone(oracle);
+        let request = request.to_vec();
+        // spread the remainder over the first few threads
+        let share = samples / threads + if t < samples % threads { 1 } else { 0 };
+
+        thread::spawn(move || sample(&*oracle, &request, share))
+    }).collect();
+
+    let mut total = [[0u64; 256]; BIASES.len()];
+
+    for handle in handles {
+        let counts = handle.join().unwrap();
+
+        for (total, count) in total.iter_mut().zip(counts.iter()) {
+            for (t, c) in total.iter_mut().zip(count.iter()) {
+                *t += c;
+            }
+        }
+    }
+
+    total
+}
+
+// Add the counts for a request padded with `padding` bytes to scores, where
+// scores[i][p] is the evidence for cookie byte i being p
+fn tally(scores: &mut [[u64; 256]], counts: &Counts, padding: usize) {
+    for (count, (pos, bias)) in counts.iter().zip(BIASES.iter()) {
+        let index = match pos.checked_sub(padding) {
+            Some(index) if index < scores.len() => index,
+            _ => continue,
+        };
+
+        for (c, n) in count.iter().enumerate() {
+            scores[index][c ^ *bias as usize] += n;
+        }
+    }
+}
+
+// The best supported value for every cookie byte
+fn best_bytes(scores: &[[u64; 256]]) -> Vec<u8> {
+    scores.iter()
+        .map(|score| (0..256).max_by_key(|p| score[*p]).unwrap() as u8)
+        .collect()
+}
+
+// Recover the secret the oracle appends to our request, using `samples`
+// encryptions per request length split over `threads` threads. Secrets up
+// to 32 bytes long can be reached.
+pub fn recover_cookie<O>(oracle: O, samples: u64, threads: u64) -> Vec<u8>
+    where O: Rc4Oracle + Send + Sync + 'static {
+    let cookie_len = oracle.encrypt(&[]).len();
+    let last_pos = BIASES[BIASES.len() - 1].0;
+
+    assert!(cookie_len <= last_pos + 1, "cookie is too long");
+
+    let oracle = Arc::new(oracle);
+    let mut scores = vec![[0u64; 256]; cookie_len];
+
+    for padding in 0..=last_pos {
+        let counts = sample_parallel(&oracle, &vec![b'A'; padding], samples, threads);
+
+        tally(&mut scores, &counts, padding);
+    }
+
+    best_bytes(&scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle() {
+        let oracle = CookieOracle::new(b"secret");
+
+        assert_eq!(oracle.encrypt(b"").len(), 6);
+        assert_eq!(oracle.encrypt(b"AAAA").len(), 10);
+        assert_ne!(oracle.encrypt(b"AAAA"), oracle.encrypt(b"AAAA"));
+    }
+
+    #[test]
+    fn test_sample() {
+        let counts = sample(&CookieOracle::new(&[0u8; 32]), &[], 1 << 12);
+
+        for count in counts.iter() {
+            assert_eq!(count.iter().sum::<u64>(), 1 << 12);
+        }
+
+        // The request is too short to reach the biased positions
+        let counts = sample(&CookieOracle::new(b"short"), &[], 16);
+        assert!(counts.iter().all(|count| count.iter().all(|c| *c == 0)));
+    }
+
+    #[test]
+    fn test_tally() {
+        // Made up counts: a flat background with the biased keystream value
+        // showing up a bit more at every biased position the cookie reaches
+        let cookie = b"the cookie!";
+        let mut scores = vec![[0u64; 256]; cookie.len()];
+
+        for padding in 0..=BIASES[BIASES.len() - 1].0 {
+            let mut counts = [[100u64; 256]; BIASES.len()];
+
+            for (count, (pos, bias)) in counts.iter_mut().zip(BIASES.iter()) {
+                if let Some(c) = pos.checked_sub(padding).and_then(|i| cookie.get(i)) {
+                    count[(c ^ bias) as usize] += 3;
+                }
+
+                // noise that doesn't line up across paddings
+                count[(padding * 7 + 1) % 256] += 2;
+            }
+
+            tally(&mut scores, &counts, padding);
+        }
+
+        assert_eq!(best_bytes(&scores), cookie.to_vec());
+    }
+}
@@ -2,9 +2,37 @@ use ramp::int::Int;
 use crate::prime::gen_prime;
 use crate::ops::IntOpsExt;
 use crate::asn1::*;
-use crate::md4::md4_digest;
+use crate::util::random_bytes;
+use crate::digest::Digest;
+use crate::md4::Md4;
+use crate::sha1::Sha1;
+use crate::sha256::{Sha224, Sha256};
 
 const MD4_OID: &[usize] = &[1, 2, 840, 113549, 2, 4];
+const SHA1_OID: &[usize] = &[1, 3, 14, 3, 2, 26];
+const SHA256_OID: &[usize] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+const SHA224_OID: &[usize] = &[2, 16, 840, 1, 101, 3, 4, 2, 4];
+
+// Hashes that can be identified in a PKCS #1 v1.5 DigestInfo
+pub trait SigDigest: Digest {
+    const OID: &'static [usize];
+}
+
+impl SigDigest for Md4 {
+    const OID: &'static [usize] = MD4_OID;
+}
+
+impl SigDigest for Sha1 {
+    const OID: &'static [usize] = SHA1_OID;
+}
+
+impl SigDigest for Sha256 {
+    const OID: &'static [usize] = SHA256_OID;
+}
+
+impl SigDigest for Sha224 {
+    const OID: &'static [usize] = SHA224_OID;
+}
 
 #[derive(Debug)]
 pub struct RSAPubKey {
@@ -66,13 +94,19 @@ pub fn gen_rsa_pair(bits: usize) -> (RSAPubKey, RSAPrivKey) {
     let p = gen_good_prime(&e, bits / 2);
     let q = gen_good_prime(&e, bits / 2);
 
+    rsa_pair_from_primes(p, q, e).unwrap()
+}
+
+// Build a key pair from given primes and public exponent. None if e isn't
+// invertible mod (p - 1)(q - 1).
+pub fn rsa_pair_from_primes(p: Int, q: Int, e: Int) -> Option<(RSAPubKey, RSAPrivKey)> {
     let n = &p * &q;
 
-    let et = (&p - 1) * (&q - 1); 
+    let et = (&p - 1) * (&q - 1);
 
-    let d = e.inv_mod(&et).unwrap();
+    let d = e.inv_mod(&et)?;
 
-    (RSAPubKey { e: e.clone(), n: n.clone() }, RSAPrivKey { p, q, n, e, d })
+    Some((RSAPubKey { e: e.clone(), n: n.clone() }, RSAPrivKey { p, q, n, e, d }))
 }
 
 pub fn encrypt_rsa(key: &RSAPubKey, m: &Int) -> Result<Int, ()> {
@@ -92,6 +126,51 @@ pub fn decrypt_rsa(key: &RSAPrivKey, c: &Int) -> Result<Int, ()> {
 	}
 }
 
+// Encryption block formatting from PKCS #1 v1.5 (block type 02).
+// k is the length of the modulus in octets.
+pub fn pkcs1_v15_pad_encrypt(msg: &[u8], k: usize) -> Result<Vec<u8>, ()> {
+    // at least 8 bytes of padding are required
+    if msg.len() + 11 > k {
+        return Err(());
+    }
+
+    let pad_len = k - 3 - msg.len();
+
+    let mut enc_block = Vec::with_capacity(k);
+    enc_block.push(0x00);
+    enc_block.push(0x02); // Block type 0x02 means padding is random non-zero bytes
+
+    while enc_block.len() < pad_len + 2 {
+        enc_block.extend(random_bytes(pad_len + 2 - enc_block.len()).iter().filter(|x| **x != 0));
+    }
+
+    enc_block.push(0x00);
+    enc_block.extend_from_slice(msg);
+
+    Ok(enc_block)
+}
+
+pub fn pkcs1_v15_unpad(block: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = block.iter();
+
+    if *bytes.next()? != 0x00 {
+        return None;
+    }
+
+    if *bytes.next()? != 0x02 {
+        return None;
+    }
+
+    let data_start = 3 + bytes.position(|x| *x == 0x00)?;
+
+    // padding string must be at least 8 bytes long
+    if data_start < 11 {
+        return None;
+    }
+
+    Some(block[data_start..].to_vec())
+}
+
 pub fn find_asn1_start(block: &[u8]) -> Option<usize> {
     let mut bytes = block.iter();
 
@@ -124,8 +203,8 @@ impl From<ASN1Error> for SigError {
     }
 }
 
-pub fn verify_rsa(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> Result<(), SigError> {
-    let digest_verify = md4_digest(msg);
+pub fn verify_rsa_with<D: SigDigest>(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> Result<(), SigError> {
+    let digest_verify = D::digest(msg);
     let y = Int::from_bytes(&sig);
     let x = encrypt_rsa(key, &y).unwrap();
     let block = x.to_bytes();
@@ -141,7 +220,7 @@ pub fn verify_rsa(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> Result<(), SigErro
     let (seq, oid) = decode_asn1_oid(&seq).unwrap();
     let (_, digest) = decode_asn1_octet_str(&seq).unwrap();
 
-    if oid != MD4_OID {
+    if oid != D::OID {
         return Err(SigError::UnsupportedDigest);
     }
 
@@ -153,15 +232,18 @@ pub fn verify_rsa(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> Result<(), SigErro
     }
 }
 
-pub fn sign_rsa(key: &RSAPrivKey, msg: &[u8]) -> Vec<u8> {
-    let digest = md4_digest(msg);
+pub fn verify_rsa(key: &RSAPubKey, msg: &[u8], sig: &[u8]) -> Result<(), SigError> {
+    verify_rsa_with::<Md4>(key, msg, sig)
+}
+
+// Signature block formatting from PKCS #1 v1.5 (block type 01).
+// k is the length of the modulus in octets.
+pub fn pkcs1_v15_pad_sign<D: SigDigest>(msg: &[u8], k: usize) -> Vec<u8> {
+    let digest = D::digest(msg);
 
     let digest_info = encode_asn1_sequence(
-        &[encode_asn1_oid(MD4_OID),
+        &[encode_asn1_oid(D::OID),
         encode_asn1_octet_str(&digest)]);
-    
-    // calculate length of n in octets
-    let k = (key.n.bit_length() as usize + 7) / 8;
 
     let pad_len = k - 3 - digest_info.len();
 
@@ -172,13 +254,24 @@ pub fn sign_rsa(key: &RSAPrivKey, msg: &[u8]) -> Vec<u8> {
     enc_block.push(0x00);
     enc_block.extend_from_slice(&digest_info);
 
-    let x = Int::from_bytes(&enc_block);
+    enc_block
+}
+
+pub fn sign_rsa_with<D: SigDigest>(key: &RSAPrivKey, msg: &[u8]) -> Vec<u8> {
+    // calculate length of n in octets
+    let k = (key.n.bit_length() as usize + 7) / 8;
+
+    let x = Int::from_bytes(&pkcs1_v15_pad_sign::<D>(msg, k));
 
     let y = decrypt_rsa(key, &x).unwrap();
 
     y.to_bytes()
 }
 
+pub fn sign_rsa(key: &RSAPrivKey, msg: &[u8]) -> Vec<u8> {
+    sign_rsa_with::<Md4>(key, msg)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,4 +286,33 @@ mod tests {
 
         verify_rsa(&pub_key, msg, &sig).unwrap();
     }
+
+    #[test]
+    fn test_sign_verify_digests() {
+        let msg = b"foobar";
+
+        let (pub_key, priv_key) = gen_rsa_pair(1024);
+
+        let sig = sign_rsa_with::<Sha1>(&priv_key, msg);
+        verify_rsa_with::<Sha1>(&pub_key, msg, &sig).unwrap();
+        assert!(verify_rsa_with::<Sha256>(&pub_key, msg, &sig).is_err());
+
+        let sig = sign_rsa_with::<Sha256>(&priv_key, msg);
+        verify_rsa_with::<Sha256>(&pub_key, msg, &sig).unwrap();
+        assert!(verify_rsa_with::<Sha256>(&pub_key, b"foobaz", &sig).is_err());
+    }
+
+    #[test]
+    fn test_pkcs1_v15_encrypt_pad() {
+        let msg = b"kick it, CC";
+
+        let block = pkcs1_v15_pad_encrypt(msg, 32).unwrap();
+
+        assert_eq!(block.len(), 32);
+        assert_eq!(&block[..2], &[0x00, 0x02]);
+        assert_eq!(pkcs1_v15_unpad(&block), Some(msg.to_vec()));
+
+        assert!(pkcs1_v15_pad_encrypt(&[0u8; 22], 32).is_err());
+        assert_eq!(pkcs1_v15_unpad(&block[1..]), None);
+    }
 }
@@ -0,0 +1,157 @@
+// Second preimages for long messages with expandable messages
+// (Kelsey and Schneier, https://www.schneier.com/wp-content/uploads/2016/02/paper-preimages.pdf)
+//
+// A long target message passes through a lot of intermediate states and
+// hitting any of them is much cheaper than hitting the final hash. The
+// length padding would normally stop us from splicing in at a different
+// position, so we build an expandable message: a set of collisions that
+// can produce a prefix of any length in a range, all ending in the same
+// state. That lets the forgery come out exactly as long as the target.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::md::{Compression, md_iterate};
+use crate::multicollision::find_state_collision;
+use crate::util::random_bytes;
+
+// k pairs of colliding messages where pair i is either a single block or
+// 2^(k-1-i) + 1 blocks, so the whole thing can be anywhere from k to
+// k + 2^k - 1 blocks long
+pub struct ExpandableMessage<C: Compression> {
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    pub final_state: C::State,
+}
+
+impl<C> ExpandableMessage<C> where C: Compression, C::State: Eq + Hash {
+    pub fn new(compression: &C, state: &C::State, k: usize) -> Self {
+        let mut pairs = Vec::with_capacity(k);
+        let mut state = state.clone();
+
+        for i in 0..k {
+            let dummy = vec![0u8; (1 << (k - 1 - i)) * compression.block_size()];
+            let dummy_state = md_iterate(compression, &state, &dummy);
+
+            let (short, long_block, next) = find_state_collision(compression, &state, &dummy_state);
+
+            let mut long = dummy;
+            long.extend_from_slice(&long_block);
+
+            pairs.push((short, long));
+            state = next;
+        }
+
+        ExpandableMessage { pairs, final_state: state }
+    }
+
+    pub fn min_blocks(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn max_blocks(&self) -> usize {
+        self.pairs.len() + (1 << self.pairs.len()) - 1
+    }
+
+    // A message of exactly `blocks` blocks ending in final_state
+    pub fn produce(&self, blocks: usize) -> Vec<u8> {
+        assert!(blocks >= self.min_blocks() && blocks <= self.max_blocks());
+
+        let k = self.pairs.len();
+        let extra = blocks - k;
+
+        self.pairs.iter()
+            .enumerate()
+            .flat_map(|(i, (short, long))| {
+                if extra & (1 << (k - 1 - i)) == 0 { short.iter() } else { long.iter() }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+// Map every intermediate state of msg to the number of blocks it took to
+// get there. Only positions we can reach with the expandable message and
+// one more block are interesting.
+fn intermediate_states<C>(compression: &C, msg: &[u8], min_blocks: usize, max_blocks: usize) -> HashMap<C::State, usize>
+    where C: Compression, C::State: Eq + Hash {
+    let mut states = HashMap::new();
+    let mut state = compression.initial_state();
+
+    for (i, block) in msg.chunks_exact(compression.block_size()).enumerate() {
+        compression.compress(&mut state, block);
+
+        let blocks = i + 1;
+
+        if blocks > min_blocks && blocks <= max_blocks + 1 {
+            states.insert(state.clone(), blocks);
+        }
+    }
+
+    states
+}
+
+// Find a message of the same length as target with the same hash. target
+// should be a lot longer than 2^k blocks' worth of states to make the
+// bridge block search cheap, and at least k + 2 blocks long.
+pub fn second_preimage<C>(compression: &C, target: &[u8], k: usize) -> Vec<u8>
+    where C: Compression, C::State: Eq + Hash {
+    let block_size = compression.block_size();
+
+    let expandable = ExpandableMessage::new(compression, &compression.initial_state(), k);
+    let states = intermediate_states(compression, target, expandable.min_blocks(), expandable.max_blocks());
+
+    assert!(!states.is_empty(), "target message is too short");
+
+    // Search for a block taking the expandable message's final state into
+    // one of the target's intermediate states
+    let (bridge, blocks) = loop {
+        let block = random_bytes(block_size);
+
+        let mut state = expandable.final_state.clone();
+        compression.compress(&mut state, &block);
+
+        if let Some(blocks) = states.get(&state) {
+            break (block, *blocks);
+        }
+    };
+
+    let mut forged = expandable.produce(blocks - 1);
+    forged.extend_from_slice(&bridge);
+    forged.extend_from_slice(&target[blocks * block_size..]);
+
+    forged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::md_digest;
+    use crate::toy_hash::ToyCompression;
+
+    #[test]
+    fn test_expandable_message() {
+        let compression = ToyCompression::new(16);
+        let expandable = ExpandableMessage::new(&compression, &compression.initial_state(), 4);
+
+        assert_eq!((expandable.min_blocks(), expandable.max_blocks()), (4, 19));
+
+        for blocks in 4..20 {
+            let msg = expandable.produce(blocks);
+
+            assert_eq!(msg.len(), blocks * compression.block_size());
+            assert_eq!(md_iterate(&compression, &compression.initial_state(), &msg), expandable.final_state);
+        }
+    }
+
+    #[test]
+    fn test_second_preimage() {
+        let compression = ToyCompression::new(16);
+        let target = random_bytes(256 * compression.block_size() + 5);
+
+        let forged = second_preimage(&compression, &target, 8);
+
+        assert_ne!(forged, target);
+        assert_eq!(forged.len(), target.len());
+        assert_eq!(md_digest(compression, &forged), md_digest(compression, &target));
+    }
+}
@@ -2,7 +2,9 @@
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
-use crate::ops::xor;
+use crate::md::{Compression, LengthEndian, MerkleDamgard, md_digest};
+use crate::digest::Digest;
+use crate::hmac::hmac;
 
 // Note 1: All variables are unsigned 32-bit quantities and wrap modulo 232 when calculating, except for
 //         ml, the message length, which is a 64-bit quantity, and
@@ -97,23 +99,25 @@ pub fn sha1_digest_chunk(h: &mut [u32; 5], chunk: &[u8]) {
 
 }
 
-pub fn sha1_digest(data: &[u8]) -> Vec<u8> {
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha1Compression;
+
+impl Compression for Sha1Compression {
+	type State = [u32; 5];
+
+	fn block_size(&self) -> usize {
+		CHUNK_SIZE
+	}
+
 	// Pre-processing:
 	// append the bit '1' to the message e.g. by adding 0x80 if message length is a multiple of 8 bits.
 	// append 0 ≤ k < 512 bits '0', such that the resulting message length in bits
 	//    is congruent to −64 ≡ 448 (mod 512)
 	// append ml, the original message length, as a 64-bit big-endian integer. 
 	//    Thus, the total length is a multiple of 512 bits.
-	let mut final_data = Vec::new();
-	final_data.extend_from_slice(&data);	
-	final_data.push(0x80);
-
-	let padding = ((CHUNK_SIZE - final_data.len() % CHUNK_SIZE) + CHUNK_SIZE - 8) % CHUNK_SIZE;
-
-	final_data.extend_from_slice(&vec![0u8; padding]);
-	final_data.write_u64::<BigEndian>((data.len() * 8) as u64).unwrap();
-
-	assert_eq!(final_data.len() % CHUNK_SIZE, 0);
+	fn length_endian(&self) -> LengthEndian {
+		LengthEndian::Big
+	}
 
 	// Initialize variables:
 
@@ -122,47 +126,76 @@ pub fn sha1_digest(data: &[u8]) -> Vec<u8> {
 	// h2 = 0x98BADCFE
 	// h3 = 0x10325476
 	// h4 = 0xC3D2E1F0
-	let mut h = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
-	for chunk in final_data.chunks(CHUNK_SIZE) {
-		sha1_digest_chunk(&mut h, &chunk);
+	fn initial_state(&self) -> [u32; 5] {
+		[0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0]
+	}
+
+	fn compress(&self, state: &mut [u32; 5], block: &[u8]) {
+		sha1_digest_chunk(state, block);
 	}
 
 	// Produce the final hash value (big-endian) as a 160-bit number:
 	// hh = (h0 leftshift 128) or (h1 leftshift 96) or (h2 leftshift 64) or (h3 leftshift 32) or h4
-	let mut hh = Vec::new();
+	fn state_to_bytes(&self, state: &[u32; 5]) -> Vec<u8> {
+		let mut hh = Vec::new();
+
+		for val in state.iter() {
+			hh.write_u32::<BigEndian>(*val).unwrap();
+		}
 
-	for val in h.iter() {
-		hh.write_u32::<BigEndian>(*val).unwrap();
+		hh
 	}
 
-	hh
+	fn state_from_bytes(&self, bytes: &[u8]) -> [u32; 5] {
+		assert_eq!(bytes.len(), HASH_SIZE);
+
+		let mut rdr = Cursor::new(bytes);
+		let mut h = [0u32; 5];
+
+		for val in h.iter_mut() {
+			*val = rdr.read_u32::<BigEndian>().unwrap();
+		}
+
+		h
+	}
 }
 
-pub fn sha1_hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
-	// if key is too long, shorten it by hashing
-	let mut key_pad =
-		if key.len() > CHUNK_SIZE {
-			sha1_digest(&key)
-		} else {
-			key.to_vec()
-		};
+// Incremental SHA-1 hasher
+#[derive(Clone)]
+pub struct Sha1 {
+	inner: MerkleDamgard<Sha1Compression>,
+}
+
+impl Sha1 {
+	// Resume from an intermediate state h after processed_len bytes
+	pub fn with_state(h: [u32; 5], processed_len: u64) -> Self {
+		Sha1 { inner: MerkleDamgard::with_state(Sha1Compression, h, processed_len) }
+	}
+}
 
-	// pad key to block size
-	while key_pad.len() < CHUNK_SIZE {
-		key_pad.push(0);
+impl Digest for Sha1 {
+	const BLOCK_SIZE: usize = CHUNK_SIZE;
+	const OUTPUT_SIZE: usize = HASH_SIZE;
+
+	fn new() -> Self {
+		Sha1 { inner: MerkleDamgard::new(Sha1Compression) }
 	}
 
-	let mut o_key_pad = xor(&key, &[0x5c; CHUNK_SIZE]);
-	let mut i_key_pad = xor(&key, &[0x36; CHUNK_SIZE]);
+	fn update(&mut self, data: &[u8]) {
+		self.inner.update(data);
+	}
 
-	// hash inner
-	i_key_pad.extend_from_slice(&data);
-	let i_hash = sha1_digest(&i_key_pad);
+	fn finalize(self) -> Vec<u8> {
+		self.inner.finalize()
+	}
+}
 
-	// hash outer
-	o_key_pad.extend_from_slice(&i_hash);
+pub fn sha1_digest(data: &[u8]) -> Vec<u8> {
+	md_digest(Sha1Compression, data)
+}
 
-	sha1_digest(&o_key_pad)
+pub fn sha1_hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+	hmac::<Sha1>(key, data)
 }
 
 #[cfg(test)]
@@ -0,0 +1,264 @@
+// from pseudo code at https://en.wikipedia.org/wiki/SHA-2
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::Cursor;
+use crate::md::{Compression, LengthEndian, MerkleDamgard, md_digest};
+use crate::digest::Digest;
+
+pub const CHUNK_SIZE: usize = 64;
+pub const HASH_SIZE: usize = 32;
+pub const SHA224_HASH_SIZE: usize = 28;
+
+// first 32 bits of the fractional parts of the cube roots of the first 64 primes
+const K: [u32; 64] = [
+    0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5, 0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+    0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3, 0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+    0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc, 0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+    0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7, 0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+    0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13, 0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+    0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3, 0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+    0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5, 0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+    0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208, 0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+pub fn sha256_digest_chunk(h: &mut [u32; 8], chunk: &[u8]) {
+    assert_eq!(chunk.len(), CHUNK_SIZE);
+
+    let mut rdr = Cursor::new(chunk);
+    let mut w = [0u32; 64];
+
+    // copy chunk into first 16 words w[0..15] of the message schedule array
+    for wi in w.iter_mut().take(16) {
+        *wi = rdr.read_u32::<BigEndian>().unwrap();
+    }
+
+    // Extend the first 16 words into the remaining 48 words w[16..63]
+    for i in 16..64 {
+        let s0 = w[i-15].rotate_right(7) ^ w[i-15].rotate_right(18) ^ (w[i-15] >> 3);
+        let s1 = w[i-2].rotate_right(17) ^ w[i-2].rotate_right(19) ^ (w[i-2] >> 10);
+
+        w[i] = w[i-16].wrapping_add(s0).wrapping_add(w[i-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+
+    // Compression function main loop
+    for (ki, wi) in K.iter().zip(w.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ ((!e) & g);
+        let temp1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(*ki).wrapping_add(*wi);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    // Add the compressed chunk to the current hash value
+    for (old_v, new_v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh].iter()) {
+        *old_v = (*old_v).wrapping_add(*new_v);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256Compression;
+
+impl Compression for Sha256Compression {
+    type State = [u32; 8];
+
+    fn block_size(&self) -> usize {
+        CHUNK_SIZE
+    }
+
+    fn length_endian(&self) -> LengthEndian {
+        LengthEndian::Big
+    }
+
+    // first 32 bits of the fractional parts of the square roots of the first 8 primes
+    fn initial_state(&self) -> [u32; 8] {
+        [0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a, 0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19]
+    }
+
+    fn compress(&self, state: &mut [u32; 8], block: &[u8]) {
+        sha256_digest_chunk(state, block);
+    }
+
+    fn state_to_bytes(&self, state: &[u32; 8]) -> Vec<u8> {
+        state_to_bytes(state)
+    }
+
+    fn state_from_bytes(&self, bytes: &[u8]) -> [u32; 8] {
+        state_from_bytes(bytes)
+    }
+}
+
+// SHA-224 is SHA-256 with a different initial state and the output
+// truncated to 7 words
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha224Compression;
+
+impl Compression for Sha224Compression {
+    type State = [u32; 8];
+
+    fn block_size(&self) -> usize {
+        CHUNK_SIZE
+    }
+
+    fn length_endian(&self) -> LengthEndian {
+        LengthEndian::Big
+    }
+
+    // second 32 bits of the fractional parts of the square roots of the 9th through 16th primes
+    fn initial_state(&self) -> [u32; 8] {
+        [0xc105_9ed8, 0x367c_d507, 0x3070_dd17, 0xf70e_5939, 0xffc0_0b31, 0x6858_1511, 0x64f9_8fa7, 0xbefa_4fa4]
+    }
+
+    fn compress(&self, state: &mut [u32; 8], block: &[u8]) {
+        sha256_digest_chunk(state, block);
+    }
+
+    fn state_to_bytes(&self, state: &[u32; 8]) -> Vec<u8> {
+        let mut digest = state_to_bytes(state);
+        digest.truncate(SHA224_HASH_SIZE);
+
+        digest
+    }
+
+    // The digest drops the last word so this only accepts a full
+    // 32 byte state, e.g. one exported from an instrumented run
+    fn state_from_bytes(&self, bytes: &[u8]) -> [u32; 8] {
+        state_from_bytes(bytes)
+    }
+}
+
+fn state_to_bytes(state: &[u32; 8]) -> Vec<u8> {
+    let mut digest = Vec::new();
+
+    for val in state.iter() {
+        digest.write_u32::<BigEndian>(*val).unwrap();
+    }
+
+    digest
+}
+
+fn state_from_bytes(bytes: &[u8]) -> [u32; 8] {
+    assert_eq!(bytes.len(), HASH_SIZE);
+
+    let mut rdr = Cursor::new(bytes);
+    let mut h = [0u32; 8];
+
+    for val in h.iter_mut() {
+        *val = rdr.read_u32::<BigEndian>().unwrap();
+    }
+
+    h
+}
+
+// Incremental SHA-256 hasher
+#[derive(Clone)]
+pub struct Sha256 {
+    inner: MerkleDamgard<Sha256Compression>,
+}
+
+impl Sha256 {
+    // Resume from an intermediate state h after processed_len bytes
+    pub fn with_state(h: [u32; 8], processed_len: u64) -> Self {
+        Sha256 { inner: MerkleDamgard::with_state(Sha256Compression, h, processed_len) }
+    }
+}
+
+impl Digest for Sha256 {
+    const BLOCK_SIZE: usize = CHUNK_SIZE;
+    const OUTPUT_SIZE: usize = HASH_SIZE;
+
+    fn new() -> Self {
+        Sha256 { inner: MerkleDamgard::new(Sha256Compression) }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.inner.finalize()
+    }
+}
+
+pub fn sha256_digest(data: &[u8]) -> Vec<u8> {
+    md_digest(Sha256Compression, data)
+}
+
+// Incremental SHA-224 hasher
+#[derive(Clone)]
+pub struct Sha224 {
+    inner: MerkleDamgard<Sha224Compression>,
+}
+
+impl Sha224 {
+    // Resume from an intermediate state h after processed_len bytes
+    pub fn with_state(h: [u32; 8], processed_len: u64) -> Self {
+        Sha224 { inner: MerkleDamgard::with_state(Sha224Compression, h, processed_len) }
+    }
+}
+
+impl Digest for Sha224 {
+    const BLOCK_SIZE: usize = CHUNK_SIZE;
+    const OUTPUT_SIZE: usize = SHA224_HASH_SIZE;
+
+    fn new() -> Self {
+        Sha224 { inner: MerkleDamgard::new(Sha224Compression) }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.inner.finalize()
+    }
+}
+
+pub fn sha224_digest(data: &[u8]) -> Vec<u8> {
+    md_digest(Sha224Compression, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_digest() {
+        assert_eq!(
+            hex::encode(&sha256_digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(
+            hex::encode(&sha256_digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hex::encode(&sha256_digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+        assert_eq!(
+            hex::encode(&sha256_digest(&[0x61; 1_000_000])),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+    }
+
+    #[test]
+    fn test_sha224_digest() {
+        assert_eq!(
+            hex::encode(&sha224_digest(b"")),
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f");
+        assert_eq!(
+            hex::encode(&sha224_digest(b"abc")),
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7");
+        assert_eq!(
+            hex::encode(&sha224_digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525");
+    }
+}
@@ -0,0 +1,134 @@
+// Small subgroup confinement (Lim and Lee, "A Key Recovery Attack on
+// Discrete Log-based Schemes Using a Prime Order Subgroup")
+//
+// If Bob doesn't check that our public key is in the subgroup of order q,
+// we can send him an element h of small order r instead. The shared secret
+// h^x then only has r possible values, so a MAC keyed with it tells us x
+// mod r. Doing this for enough small factors r of (p - 1) / q and combining
+// the residues with the CRT gives x.
+
+use ramp::int::Int;
+
+use crate::crypto_helper::hmac_sha256;
+use crate::dh::{DhGroup, gen_session_key};
+use crate::ops::{IntOpsExt, crt};
+use crate::prime::factor_small;
+
+pub const MESSAGE: &[u8] = b"crazy flamboyant for the rap enjoyment";
+
+fn mac(shared: &Int, msg: &[u8]) -> Vec<u8> {
+    hmac_sha256(&shared.to_bytes(), msg)
+}
+
+pub struct Bob {
+    group: DhGroup,
+    priv_key: Int,
+    pub pub_key: Int,
+}
+
+impl Bob {
+    pub fn new(group: &DhGroup) -> Self {
+        let (pub_key, priv_key) = group.gen_pair();
+
+        Bob { group: group.clone(), priv_key, pub_key }
+    }
+
+    // Bob doesn't validate the public key he's given
+    pub fn respond(&self, pub_key: &Int) -> (Vec<u8>, Vec<u8>) {
+        let shared = gen_session_key(pub_key, &self.priv_key, &self.group.p);
+
+        (MESSAGE.to_vec(), mac(&shared, MESSAGE))
+    }
+
+    pub fn check_key(&self, priv_key: &Int) -> bool {
+        *priv_key == self.priv_key
+    }
+}
+
+// Find x mod r given a MAC over msg keyed with h^x, h being of order r
+pub fn brute_force_residue(group: &DhGroup, h: &Int, r: u64, msg: &[u8], tag: &[u8]) -> Option<u64> {
+    let mut shared = Int::from(1);
+
+    for x in 0..r {
+        if mac(&shared, msg) == tag {
+            return Some(x);
+        }
+
+        shared = (shared * h) % &group.p;
+    }
+
+    None
+}
+
+// Residues of Bob's key modulo small factors of (p - 1) / q, stopping once
+// the product of the moduli passes q
+pub fn collect_residues(group: &DhGroup, bob: &Bob, bound: u64) -> Vec<(Int, Int)> {
+    let mut residues = Vec::new();
+    let mut modulus = Int::from(1);
+
+    for r in factor_small(&group.cofactor(), bound) {
+        let r_int = Int::from(r);
+
+        // an r dividing q as well wouldn't confine anything
+        if &group.q % &r_int == 0 {
+            continue;
+        }
+
+        let h = group.element_of_order(&r_int);
+        let (msg, tag) = bob.respond(&h);
+        let x = brute_force_residue(group, &h, r, &msg, &tag).expect("no residue matches the MAC");
+
+        modulus *= &r_int;
+        residues.push((Int::from(x), r_int));
+
+        if modulus > group.q {
+            break;
+        }
+    }
+
+    residues
+}
+
+// Recover Bob's private key. Returns None if the small factors of
+// (p - 1) / q below bound don't cover q.
+pub fn recover_key(group: &DhGroup, bob: &Bob, bound: u64) -> Option<Int> {
+    let residues = collect_residues(group, bob, bound);
+    let (x, modulus) = crt(&residues);
+
+    if modulus > group.q {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dh::CH57_GROUP;
+
+    #[test]
+    fn test_brute_force_residue() {
+        let group = &*CH57_GROUP;
+        let bob = Bob::new(group);
+
+        let r = 109;
+        let h = group.element_of_order(&Int::from(r));
+        let (msg, tag) = bob.respond(&h);
+
+        let x = brute_force_residue(group, &h, r, &msg, &tag).unwrap();
+
+        assert_eq!(h.pow_mod(&Int::from(x), &group.p), h.pow_mod(&bob.priv_key, &group.p));
+    }
+
+    #[test]
+    fn test_recover_key() {
+        let group = &*CH57_GROUP;
+        let bob = Bob::new(group);
+
+        let x = recover_key(group, &bob, 1 << 16).unwrap();
+
+        assert!(bob.check_key(&x));
+        assert_eq!(recover_key(group, &bob, 1000), None);
+    }
+}
@@ -0,0 +1,71 @@
+// Deliberately weak Merkle-Damgard hash for the iterated hash attacks
+//
+// The compression function pads the chaining state out to an AES-128 key,
+// encrypts the message block with it and keeps the first width_bits of
+// the result, so collisions are cheap to find by brute force.
+
+use crate::crypto_helper::{encrypt_ecb, BLOCK_SIZE};
+use crate::md::{Compression, LengthEndian, md_digest};
+
+const TOY_IV: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToyCompression {
+    width_bits: usize,
+}
+
+impl ToyCompression {
+    // width_bits has to be a whole number of bytes and at most 64
+    pub fn new(width_bits: usize) -> Self {
+        assert!(width_bits % 8 == 0 && width_bits > 0 && width_bits <= 64);
+
+        ToyCompression { width_bits }
+    }
+
+    pub fn width_bits(&self) -> usize {
+        self.width_bits
+    }
+
+    pub fn width_bytes(&self) -> usize {
+        self.width_bits / 8
+    }
+}
+
+impl Compression for ToyCompression {
+    type State = Vec<u8>;
+
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn length_endian(&self) -> LengthEndian {
+        LengthEndian::Big
+    }
+
+    fn initial_state(&self) -> Vec<u8> {
+        TOY_IV[..self.width_bytes()].to_vec()
+    }
+
+    fn compress(&self, state: &mut Vec<u8>, block: &[u8]) {
+        let mut key = state.clone();
+        key.resize(BLOCK_SIZE, 0);
+
+        let output = encrypt_ecb(&key, block);
+
+        state.copy_from_slice(&output[..self.width_bytes()]);
+    }
+
+    fn state_to_bytes(&self, state: &Vec<u8>) -> Vec<u8> {
+        state.clone()
+    }
+
+    fn state_from_bytes(&self, bytes: &[u8]) -> Vec<u8> {
+        assert_eq!(bytes.len(), self.width_bytes());
+
+        bytes.to_vec()
+    }
+}
+
+pub fn toy_digest(width_bits: usize, data: &[u8]) -> Vec<u8> {
+    md_digest(ToyCompression::new(width_bits), data)
+}
@@ -0,0 +1,216 @@
+// Twist attack on x-only ECDH
+//
+// With the Montgomery ladder Bob can't check which curve a u coordinate
+// belongs to, since every u is on either the curve or its quadratic twist.
+// The twist's order has small factors, so the invalid curve attack works
+// with twist points instead. Two catches: u(kP) = u(-kP) so each residue is
+// only known up to sign, and the small factors don't cover the whole key,
+// so the kangaroo finishes the job on the real curve.
+
+use ramp::int::{Int, RandomInt};
+use rand;
+
+use crate::crypto_helper::hmac_sha256;
+use crate::kangaroo::ec_kangaroo_any;
+use crate::montgomery::MontgomeryCurve;
+use crate::ec::ECPoint;
+use crate::ops::{IntOpsExt, crt};
+use crate::prime::factor_small;
+
+pub const MESSAGE: &[u8] = b"crazy flamboyant for the rap enjoyment";
+
+fn mac(shared: &Int, msg: &[u8]) -> Vec<u8> {
+    hmac_sha256(&shared.to_bytes(), msg)
+}
+
+pub struct MontgomeryBob {
+    curve: MontgomeryCurve,
+    priv_key: Int,
+    pub pub_u: Int,
+}
+
+impl MontgomeryBob {
+    pub fn new(curve: &MontgomeryCurve) -> Self {
+        let priv_key = rand::thread_rng().gen_int_range(&Int::from(1), &curve.order);
+        let pub_u = curve.ladder(&curve.base_u, &priv_key);
+
+        MontgomeryBob { curve: curve.clone(), priv_key, pub_u }
+    }
+
+    // Bob only ever looks at u so he can't tell it's not on his curve
+    pub fn respond(&self, u: &Int) -> (Vec<u8>, Vec<u8>) {
+        let shared = self.curve.ladder(u, &self.priv_key);
+
+        (MESSAGE.to_vec(), mac(&shared, MESSAGE))
+    }
+
+    pub fn check_key(&self, priv_key: &Int) -> bool {
+        *priv_key == self.priv_key
+    }
+}
+
+// Find a with k = +-a mod r given a MAC keyed with u(k * P), P being a
+// point of odd order r. Walks the multiples of P with differential
+// additions, only half of them are needed.
+pub fn brute_force_residue(curve: &MontgomeryCurve, u: &Int, r: u64, msg: &[u8], tag: &[u8]) -> Option<u64> {
+    if mac(&Int::from(0), msg) == tag {
+        return Some(0);
+    }
+
+    let mut prev = Int::from(0);
+    let mut cur = u.clone();
+
+    for a in 1..=r / 2 {
+        if mac(&cur, msg) == tag {
+            return Some(a);
+        }
+
+        let next = if a == 1 { curve.x_double(u) } else { curve.x_diff_add(&cur, u, &prev) };
+
+        prev = cur;
+        cur = next;
+    }
+
+    None
+}
+
+// (a, r) pairs with k = +-a mod r for the odd small factors of the twist
+// order
+pub fn collect_residues(curve: &MontgomeryCurve, bob: &MontgomeryBob, bound: u64) -> Vec<(u64, u64)> {
+    factor_small(&curve.twist_order(), bound)
+        .into_iter()
+        .filter(|r| r % 2 != 0)
+        .map(|r| {
+            let u = curve.twist_point_of_order(&[r]);
+            let (msg, tag) = bob.respond(&u);
+
+            (brute_force_residue(curve, &u, r, &msg, &tag).expect("no residue matches the MAC"), r)
+        })
+        .collect()
+}
+
+fn crt_pair(a1: u64, r1: u64, a2: u64, r2: u64) -> Int {
+    crt(&[(Int::from(a1), Int::from(r1)), (Int::from(a2), Int::from(r2))]).0
+}
+
+// Only the combinations of signs that agree with each other are possible.
+// A point of order r1 * rj tells us whether k is +-(a1, aj) or
+// +-(a1, -aj) mod r1 * rj, so fix all signs relative to the first nonzero
+// residue. Returns (n, R) with k = +-n mod R.
+pub fn resolve_signs(curve: &MontgomeryCurve, bob: &MontgomeryBob, residues: &[(u64, u64)]) -> (Int, Int) {
+    let (a1, r1) = *residues.iter().find(|(a, _)| *a != 0).expect("every residue is zero");
+
+    let signed: Vec<_> = residues.iter()
+        .map(|(a, r)| {
+            if *a == 0 || *r == r1 {
+                return (Int::from(*a), Int::from(*r));
+            }
+
+            let u = curve.twist_point_of_order(&[r1, *r]);
+            let (msg, tag) = bob.respond(&u);
+
+            let same_sign = mac(&curve.ladder(&u, &crt_pair(a1, r1, *a, *r)), &msg) == tag;
+
+            (Int::from(if same_sign { *a } else { r - a }), Int::from(*r))
+        })
+        .collect();
+
+    crt(&signed)
+}
+
+// Finish off the key given k = +-n mod R: k = n' + m * R with n' being n
+// or -n, and m found with the kangaroo on the Weierstrass form of the
+// curve. Bob's public u only gives his point up to sign, so try both
+// points, with one tame kangaroo for all four guesses, and let the ladder
+// confirm the result.
+pub fn recover_key_from_residue(curve: &MontgomeryCurve, pub_u: &Int, n: &Int, modulus: &Int) -> Option<Int> {
+    let weierstrass = curve.to_weierstrass();
+    let pub_v = curve.v_from_u(pub_u)?;
+    let pub_key = curve.to_weierstrass_point(&ECPoint::new(pub_u.clone(), pub_v));
+
+    let g = weierstrass.scalar_mul_base(modulus);
+    let upper = (&curve.order - 1) / modulus;
+
+    let mut offsets = Vec::new();
+    let mut ys = Vec::new();
+
+    for point in [pub_key.clone(), weierstrass.negate(&pub_key)].iter() {
+        for offset in [n % modulus, (modulus - n) % modulus].iter() {
+            offsets.push(offset.clone());
+            ys.push(weierstrass.add(point, &weierstrass.negate(&weierstrass.scalar_mul_base(offset))));
+        }
+    }
+
+    let (i, m) = ec_kangaroo_any(&weierstrass, &g, &ys, &Int::from(0), &upper)?;
+    let k = &offsets[i] + m * modulus;
+
+    if curve.ladder(&curve.base_u, &k) == *pub_u {
+        Some(k)
+    } else {
+        None
+    }
+}
+
+// Recover Bob's key: k = +-n mod R from the twist, then the kangaroo for
+// the rest
+pub fn recover_key(curve: &MontgomeryCurve, bob: &MontgomeryBob, bound: u64) -> Option<Int> {
+    let residues = collect_residues(curve, bob, bound);
+    let (n, modulus) = resolve_signs(curve, bob, &residues);
+
+    recover_key_from_residue(curve, &bob.pub_u, &n, &modulus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::montgomery::SET8_MONTGOMERY;
+
+    #[test]
+    fn test_brute_force_residue() {
+        let curve = &*SET8_MONTGOMERY;
+        let bob = MontgomeryBob::new(curve);
+
+        for r in [11, 107, 197].iter() {
+            let u = curve.twist_point_of_order(&[*r]);
+            let (msg, tag) = bob.respond(&u);
+
+            let a = brute_force_residue(curve, &u, *r, &msg, &tag).unwrap();
+            let k = (&bob.priv_key % Int::from(*r)).real_mod(&Int::from(*r));
+
+            assert!(k == a || k == Int::from(r - a) % Int::from(*r));
+        }
+    }
+
+    #[test]
+    fn test_resolve_signs() {
+        let curve = &*SET8_MONTGOMERY;
+        let bob = MontgomeryBob::new(curve);
+
+        let residues = collect_residues(curve, &bob, 2000);
+        assert_eq!(residues.iter().map(|(_, r)| *r).collect::<Vec<_>>(), vec![11, 107, 197, 1621]);
+
+        let (n, modulus) = resolve_signs(curve, &bob, &residues);
+        let k = &bob.priv_key % &modulus;
+
+        assert_eq!(modulus, Int::from(11 * 107 * 197 * 1621));
+        assert!(k == n || k == (&modulus - &n) % &modulus);
+    }
+
+    #[test]
+    fn test_recover_key_from_residue() {
+        let curve = &*SET8_MONTGOMERY;
+
+        // Leave 2^12 values for the kangaroo. Bob's point comes out with
+        // either sign at random, the residue's sign alternates.
+        let modulus = (&curve.order >> 12) + 1;
+
+        for i in 0..4 {
+            let bob = MontgomeryBob::new(curve);
+            let n = &bob.priv_key % &modulus;
+            let n = if i % 2 == 0 { n } else { (&modulus - n) % &modulus };
+
+            let k = recover_key_from_residue(curve, &bob.pub_u, &n, &modulus).unwrap();
+            assert!(bob.check_key(&k));
+        }
+    }
+}